@@ -0,0 +1,142 @@
+use bevy::{
+    input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+
+use self::mode::{camera_mode_is, CameraBlend, CameraMode};
+use crate::{
+    input::{Action, ActionState},
+    screen::PlayingState,
+    AppSet,
+};
+
+pub mod mode;
+pub mod physical;
+pub mod volume;
+
+pub(crate) fn plugin(app: &mut App) {
+    app.add_plugins((mode::plugin, physical::plugin, volume::plugin));
+
+    // Spawn the main camera.
+    app.add_systems(Startup, spawn_camera)
+        .add_systems(
+            Update,
+            orbit_camera
+                .in_set(AppSet::Update)
+                .run_if(in_state(PlayingState::Running))
+                .run_if(not(camera_mode_is(CameraMode::FreeFly))),
+        )
+        .register_type::<CameraOrbit>();
+}
+
+#[derive(Component)]
+pub struct MainCamera;
+
+/// Orbit speed of the camera when the look actions are fully pressed, in radians per second.
+const ORBIT_SPEED: f32 = 2.0;
+/// Orbit speed of the camera when dragging the mouse, in radians per pixel.
+const MOUSE_SENSITIVITY: f32 = 0.005;
+/// How much one notch of the scroll wheel zooms in or out, in meters.
+const ZOOM_STEP: f32 = 0.5;
+/// Roughly how many pixels a line of scrolling amounts to, for touchpads.
+const PIXELS_PER_LINE: f32 = 16.0;
+/// Distance between the ears of the spatial audio listener, in meters.
+const EAR_GAP: f32 = 0.2;
+
+/// How far the camera orbits around the entity it follows, on top of following its rotation.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct CameraOrbit {
+    /// Rotation around the vertical axis, in radians.
+    pub yaw: f32,
+    /// Rotation around the horizontal axis, in radians. Positive values lower the camera.
+    pub pitch: f32,
+    /// Length of the camera arm, in meters, when nothing is in the way.
+    pub distance: f32,
+}
+
+impl Default for CameraOrbit {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.0,
+            distance: 5.0,
+        }
+    }
+}
+
+impl CameraOrbit {
+    const MIN_PITCH: f32 = -1.2;
+    const MAX_PITCH: f32 = 0.3;
+    const MIN_DISTANCE: f32 = 2.0;
+    const MAX_DISTANCE: f32 = 12.0;
+    /// Direction of the camera arm before the orbit rotation: behind and slightly above.
+    const ARM_DIRECTION: Vec3 = Vec3::new(0.0, 0.2, 1.0);
+    /// Radius of the sphere swept along the arm to keep the camera out of walls. Should be a bit
+    /// larger than the near clipping plane.
+    pub const COLLISION_RADIUS: f32 = 0.2;
+
+    pub fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0)
+    }
+
+    /// Offset of the camera from the point it looks at, before the orbit rotation.
+    pub fn arm_offset(&self) -> Vec3 {
+        Self::ARM_DIRECTION.normalize() * self.distance
+    }
+}
+
+fn spawn_camera(mut commands: Commands) {
+    // Camera. Its transform is driven by the rig of the active mode, see `mode`.
+    commands.spawn((
+        Name::new("3D Camera"),
+        MainCamera,
+        CameraOrbit::default(),
+        CameraBlend::default(),
+        Camera3dBundle {
+            camera: Camera {
+                hdr: true,
+                ..default()
+            },
+            ..default()
+        },
+        // Bloom and depth of field depend on the graphics settings.
+        IsDefaultUiCamera,
+        // Hear the world from where it's seen.
+        SpatialListener::new(EAR_GAP),
+    ));
+}
+
+fn orbit_camera(
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    mut orbit: Query<&mut CameraOrbit, With<MainCamera>>,
+) {
+    let mut orbit = orbit.single_mut();
+
+    let mut delta = Vec2::new(
+        actions.axis(Action::LookRight, Action::LookLeft),
+        actions.axis(Action::LookDown, Action::LookUp),
+    ) * ORBIT_SPEED
+        * time.delta_seconds();
+    // Drag with the right mouse button to orbit, so the cursor stays free for the UI.
+    let mouse_delta: Vec2 = mouse_motion.read().map(|motion| motion.delta).sum();
+    if mouse.pressed(MouseButton::Right) {
+        delta -= mouse_delta * MOUSE_SENSITIVITY;
+    }
+    orbit.yaw += delta.x;
+    orbit.pitch = (orbit.pitch + delta.y).clamp(CameraOrbit::MIN_PITCH, CameraOrbit::MAX_PITCH);
+
+    let scroll: f32 = mouse_wheel
+        .read()
+        .map(|wheel| match wheel.unit {
+            MouseScrollUnit::Line => wheel.y,
+            MouseScrollUnit::Pixel => wheel.y / PIXELS_PER_LINE,
+        })
+        .sum();
+    orbit.distance = (orbit.distance - scroll * ZOOM_STEP)
+        .clamp(CameraOrbit::MIN_DISTANCE, CameraOrbit::MAX_DISTANCE);
+}
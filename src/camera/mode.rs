@@ -0,0 +1,302 @@
+//! Camera modes and the transitions between them.
+//!
+//! Each [`CameraMode`] owns a dolly [`Rig`] on its own entity, and every rig is updated every frame
+//! so that it's ready to take over. The [`MainCamera`] copies the transform and field of view of the
+//! active rig, blending from where it was when the mode changed.
+
+use std::time::Duration;
+
+use bevy::{
+    input::mouse::MouseMotion,
+    math::{vec3, FloatExt},
+    prelude::*,
+};
+use bevy_dolly::prelude::{Arm, LookAt, Position, Rig, Rotation, Smooth, YawPitch};
+
+use super::{CameraOrbit, MainCamera};
+use crate::{
+    input::{Action, ActionState},
+    screen::PlayingState,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<CameraModes>()
+        .register_type::<CameraMode>()
+        .register_type::<CameraModes>()
+        .add_systems(Startup, spawn_rigs)
+        .add_systems(
+            Update,
+            (
+                toggle_view.in_set(AppSet::Update),
+                fly_camera
+                    .in_set(AppSet::Update)
+                    .run_if(camera_mode_is(CameraMode::FreeFly)),
+            )
+                .run_if(in_state(PlayingState::Running)),
+        )
+        // Run after the drivers have been updated for this frame.
+        .add_systems(Update, update_rigs.after(AppSet::Update))
+        .observe(set_camera_mode);
+}
+
+/// How long switching between first-person and chase takes.
+const VIEW_BLEND: Duration = Duration::from_millis(300);
+/// Speed of the free-fly camera, in meters per second.
+const FLY_SPEED: f32 = 10.0;
+/// Rotation speed of the free-fly camera when the look actions are fully pressed, in degrees per
+/// second.
+const FLY_TURN_SPEED: f32 = 120.0;
+/// Rotation speed of the free-fly camera when dragging the mouse, in degrees per pixel.
+const FLY_MOUSE_SENSITIVITY: f32 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum CameraMode {
+    /// Follows the tracked entity from behind, see [`CameraOrbit`].
+    Chase,
+    /// Looks through the eyes of the tracked entity.
+    FirstPerson,
+    /// Watches the tracked entity from a fixed point, see
+    /// [`CameraVolume`](super::volume::CameraVolume).
+    Fixed,
+    /// Flies around freely, for debugging. Player controls are disabled.
+    FreeFly,
+}
+
+/// Which camera mode is in use.
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct CameraModes {
+    /// The mode the camera is in, or blending to.
+    pub active: CameraMode,
+    /// The mode chosen by the player, which camera volumes temporarily override.
+    pub preferred: CameraMode,
+}
+
+impl Default for CameraModes {
+    fn default() -> Self {
+        Self {
+            active: CameraMode::Chase,
+            preferred: CameraMode::Chase,
+        }
+    }
+}
+
+/// Run condition that checks the active camera mode.
+pub fn camera_mode_is(mode: CameraMode) -> impl Fn(Res<CameraModes>) -> bool {
+    move |modes| modes.active == mode
+}
+
+/// Trigger this event to switch the camera to another mode.
+#[derive(Event, Debug)]
+pub struct SetCameraMode {
+    pub mode: CameraMode,
+    /// How long the transition takes. The switch is immediate if this is zero.
+    pub blend: Duration,
+}
+
+/// The rig of a camera mode.
+#[derive(Component, Debug)]
+pub struct CameraRig {
+    pub mode: CameraMode,
+    /// Vertical field of view, in radians.
+    pub fov: f32,
+}
+
+/// Where the [`MainCamera`] was when the mode last changed, to blend from.
+#[derive(Component, Debug)]
+pub(super) struct CameraBlend {
+    transform: Transform,
+    fov: f32,
+    timer: Timer,
+}
+
+impl Default for CameraBlend {
+    fn default() -> Self {
+        Self {
+            transform: Transform::default(),
+            fov: 0.0,
+            timer: Timer::new(Duration::ZERO, TimerMode::Once),
+        }
+    }
+}
+
+fn spawn_rigs(mut commands: Commands) {
+    let start_pos = vec3(0.0, 1.0, 7.0);
+    commands.spawn((
+        Name::new("Chase rig"),
+        CameraRig {
+            mode: CameraMode::Chase,
+            fov: 0.5,
+        },
+        Rig::builder()
+            .with(Position::new(start_pos))
+            .with(Rotation::new(Quat::IDENTITY))
+            .with(Smooth::new_position(1.25).predictive(true))
+            .with(Arm::new(CameraOrbit::default().arm_offset()))
+            .with(Smooth::new_position(2.5))
+            .with(
+                LookAt::new(start_pos)
+                    .tracking_smoothness(1.25)
+                    .tracking_predictive(true),
+            )
+            .build(),
+    ));
+    commands.spawn((
+        Name::new("First-person rig"),
+        CameraRig {
+            mode: CameraMode::FirstPerson,
+            fov: 1.0,
+        },
+        Rig::builder()
+            .with(Position::new(start_pos))
+            .with(Rotation::new(Quat::IDENTITY))
+            .with(Smooth::new_rotation(0.5))
+            .build(),
+    ));
+    commands.spawn((
+        Name::new("Fixed rig"),
+        CameraRig {
+            mode: CameraMode::Fixed,
+            fov: 0.5,
+        },
+        Rig::builder()
+            .with(Position::new(start_pos))
+            .with(LookAt::new(Vec3::ZERO).tracking_smoothness(1.25))
+            .build(),
+    ));
+    commands.spawn((
+        Name::new("Free-fly rig"),
+        CameraRig {
+            mode: CameraMode::FreeFly,
+            fov: std::f32::consts::FRAC_PI_4,
+        },
+        Rig::builder()
+            .with(Position::new(start_pos))
+            .with(YawPitch::new())
+            .build(),
+    ));
+}
+
+fn set_camera_mode(
+    trigger: Trigger<SetCameraMode>,
+    mut modes: ResMut<CameraModes>,
+    mut camera: Query<(&Transform, &Projection, &mut CameraBlend), With<MainCamera>>,
+    mut rigs: Query<(&mut Rig, &CameraRig)>,
+) {
+    let SetCameraMode { mode, blend } = *trigger.event();
+    let (transform, projection, mut camera_blend) = camera.single_mut();
+
+    // Blend from wherever the camera is, even in the middle of another transition.
+    *camera_blend = CameraBlend {
+        transform: *transform,
+        fov: match projection {
+            Projection::Perspective(perspective) => perspective.fov,
+            Projection::Orthographic(_) => 0.0,
+        },
+        timer: Timer::new(blend, TimerMode::Once),
+    };
+
+    // The free-fly camera takes off from the current point of view.
+    if mode == CameraMode::FreeFly && modes.active != CameraMode::FreeFly {
+        for (mut rig, camera_rig) in &mut rigs {
+            if camera_rig.mode == CameraMode::FreeFly {
+                rig.driver_mut::<Position>().position = transform.translation;
+                rig.driver_mut::<YawPitch>()
+                    .set_rotation_quat(transform.rotation);
+            }
+        }
+    }
+
+    modes.active = mode;
+}
+
+/// Switch between the chase and first-person cameras.
+fn toggle_view(mut commands: Commands, actions: Res<ActionState>, mut modes: ResMut<CameraModes>) {
+    if !actions.just_pressed(Action::ToggleView) {
+        return;
+    }
+    let previous = modes.preferred;
+    modes.preferred = match previous {
+        CameraMode::FirstPerson => CameraMode::Chase,
+        _ => CameraMode::FirstPerson,
+    };
+    // Camera volumes and the free-fly camera take precedence.
+    if modes.active == previous {
+        commands.trigger(SetCameraMode {
+            mode: modes.preferred,
+            blend: VIEW_BLEND,
+        });
+    }
+}
+
+fn fly_camera(
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut rigs: Query<(&mut Rig, &CameraRig)>,
+) {
+    let Some((mut rig, _)) = rigs
+        .iter_mut()
+        .find(|(_, camera_rig)| camera_rig.mode == CameraMode::FreeFly)
+    else {
+        return;
+    };
+    let dt = time.delta_seconds();
+
+    let mut turn = Vec2::new(
+        actions.axis(Action::LookRight, Action::LookLeft),
+        actions.axis(Action::LookDown, Action::LookUp),
+    ) * FLY_TURN_SPEED
+        * dt;
+    let mouse_delta: Vec2 = mouse_motion.read().map(|motion| motion.delta).sum();
+    if mouse.pressed(MouseButton::Right) {
+        turn -= mouse_delta * FLY_MOUSE_SENSITIVITY;
+    }
+    let yaw_pitch = rig.driver_mut::<YawPitch>();
+    yaw_pitch.rotate_yaw_pitch(turn.x, turn.y);
+    let rotation = Quat::from_euler(
+        EulerRot::YXZ,
+        yaw_pitch.yaw_degrees.to_radians(),
+        yaw_pitch.pitch_degrees.to_radians(),
+        0.0,
+    );
+
+    let movement = Vec3::new(
+        actions.axis(Action::TurnLeft, Action::TurnRight),
+        actions.axis(Action::Crouch, Action::Jump),
+        actions.axis(Action::MoveForward, Action::MoveBackward),
+    );
+    rig.driver_mut::<Position>().position +=
+        rotation * movement.clamp_length_max(1.0) * FLY_SPEED * dt;
+}
+
+/// Update every rig and move the camera to the active one.
+fn update_rigs(
+    time: Res<Time>,
+    modes: Res<CameraModes>,
+    mut rigs: Query<(&mut Rig, &CameraRig)>,
+    mut camera: Query<(&mut Transform, &mut Projection, &mut CameraBlend), With<MainCamera>>,
+) {
+    let (mut transform, mut projection, mut blend) = camera.single_mut();
+    blend.timer.tick(time.delta());
+    let t = blend.timer.fraction();
+    // Ease in and out.
+    let t = t * t * (3.0 - 2.0 * t);
+
+    for (mut rig, camera_rig) in &mut rigs {
+        let rig_transform = rig.update(time.delta_seconds());
+        if camera_rig.mode != modes.active {
+            continue;
+        }
+        transform.translation = blend
+            .transform
+            .translation
+            .lerp(rig_transform.translation, t);
+        transform.rotation = blend.transform.rotation.slerp(rig_transform.rotation, t);
+        if let Projection::Perspective(perspective) = &mut *projection {
+            perspective.fov = blend.fov.lerp(camera_rig.fov, t);
+        }
+    }
+}
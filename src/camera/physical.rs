@@ -0,0 +1,170 @@
+//! Physically based exposure and depth of field of the main camera.
+
+use bevy::{
+    core_pipeline::dof::{DepthOfFieldMode, DepthOfFieldSettings},
+    prelude::*,
+    render::camera::{Exposure, PhysicalCameraParameters},
+};
+use serde::Deserialize;
+
+#[cfg(not(target_family = "wasm"))]
+use bevy::core_pipeline::auto_exposure::{AutoExposurePlugin, AutoExposureSettings};
+
+use super::MainCamera;
+use crate::{game::spawn::player::CameraTracked, graphics::GraphicsSettings};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<CameraParameters>()
+        .register_type::<CameraPreset>()
+        .insert_resource(CameraParameters::from(CameraPreset::default()))
+        .add_systems(
+            Update,
+            update_camera.run_if(
+                resource_changed::<CameraParameters>.or_else(resource_changed::<GraphicsSettings>),
+            ),
+        )
+        // The camera has reached its final position for this frame by then.
+        .add_systems(PostUpdate, auto_focus);
+
+    // Auto exposure needs compute shaders, which WebGL2 doesn't have.
+    #[cfg(not(target_family = "wasm"))]
+    app.add_plugins(AutoExposurePlugin).add_systems(
+        Update,
+        update_auto_exposure.run_if(resource_changed::<CameraParameters>),
+    );
+
+    #[cfg(feature = "dev")]
+    app.add_plugins((bevy_inspector_egui::quick::ResourceInspectorPlugin::<
+        CameraParameters,
+    >::default(),));
+}
+
+/// How much the distance to the tracked entity has to change before refocusing, in meters.
+const FOCUS_TOLERANCE: f32 = 0.01;
+
+// PhysicalCameraParameters doesn't implement `Reflect` for some reason...
+#[derive(Default, Copy, Clone, PartialEq, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct CameraParameters {
+    aperture_f_stops: f32,
+    shutter_speed_s: f32,
+    sensitivity_iso: f32,
+    sensor_height: f32,
+    /// Adapt the exposure to the brightness of the scene, like an eye would. Not available on the
+    /// web.
+    pub auto_exposure: bool,
+}
+
+impl CameraParameters {
+    /// These parameters with the aperture, shutter speed and sensitivity of `preset`.
+    pub fn with_preset(&self, preset: CameraPreset) -> Self {
+        Self {
+            auto_exposure: self.auto_exposure,
+            ..preset.into()
+        }
+    }
+}
+
+impl From<CameraParameters> for PhysicalCameraParameters {
+    fn from(
+        CameraParameters {
+            aperture_f_stops,
+            shutter_speed_s,
+            sensitivity_iso,
+            sensor_height,
+            ..
+        }: CameraParameters,
+    ) -> Self {
+        PhysicalCameraParameters {
+            aperture_f_stops,
+            shutter_speed_s,
+            sensitivity_iso,
+            sensor_height,
+        }
+    }
+}
+
+/// Camera settings suited to common lighting conditions, which levels can pick from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect, Deserialize)]
+pub enum CameraPreset {
+    /// The "sunny 16" rule: f/16, 1/125 s, ISO 100 (EV100 15).
+    SunnyOutdoor,
+    /// f/2.8, 1/50 s, ISO 100 (EV100 8.6).
+    #[default]
+    Indoor,
+    /// f/1.4, 1/30 s, ISO 3200 (EV100 0.9).
+    Night,
+}
+
+impl From<CameraPreset> for CameraParameters {
+    fn from(preset: CameraPreset) -> Self {
+        let (aperture_f_stops, shutter_speed_s, sensitivity_iso) = match preset {
+            CameraPreset::SunnyOutdoor => (16.0, 1.0 / 125.0, 100.0),
+            CameraPreset::Indoor => (2.8, 1.0 / 50.0, 100.0),
+            CameraPreset::Night => (1.4, 1.0 / 30.0, 3200.0),
+        };
+        CameraParameters {
+            aperture_f_stops,
+            shutter_speed_s,
+            sensitivity_iso,
+            sensor_height: 0.016, // for width = 35mm
+            auto_exposure: false,
+        }
+    }
+}
+
+fn update_camera(
+    mut commands: Commands,
+    mut camera: Query<(Entity, &mut Exposure, Option<&DepthOfFieldSettings>), With<MainCamera>>,
+    params: Res<CameraParameters>,
+    graphics: Res<GraphicsSettings>,
+) {
+    let (entity, mut exposure, dof) = camera.single_mut();
+    let physical_params: PhysicalCameraParameters = (*params).into();
+    *exposure = Exposure::from_physical_camera(physical_params);
+
+    if !graphics.depth_of_field {
+        commands.entity(entity).remove::<DepthOfFieldSettings>();
+        return;
+    }
+    let mut new_dof = DepthOfFieldSettings {
+        mode: DepthOfFieldMode::Bokeh,
+        ..DepthOfFieldSettings::from_physical_camera(&physical_params)
+    };
+    if let Some(dof) = dof {
+        new_dof.focal_distance = dof.focal_distance;
+    }
+    commands.entity(entity).insert(new_dof);
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn update_auto_exposure(
+    mut commands: Commands,
+    camera: Query<Entity, With<MainCamera>>,
+    params: Res<CameraParameters>,
+) {
+    let mut camera = commands.entity(camera.single());
+    if params.auto_exposure {
+        camera.insert(AutoExposureSettings::default());
+    } else {
+        camera.remove::<AutoExposureSettings>();
+    }
+}
+
+/// Keep the tracked entity in focus.
+fn auto_focus(
+    mut camera: Query<(&Transform, &mut DepthOfFieldSettings), With<MainCamera>>,
+    tracked: Query<&Transform, With<CameraTracked>>,
+) {
+    let Ok(tracked) = tracked.get_single() else {
+        return;
+    };
+    let Ok((transform, mut dof)) = camera.get_single_mut() else {
+        return;
+    };
+    let distance = transform.translation.distance(tracked.translation);
+    // Avoid touching the component, and re-extracting it, for no reason.
+    if (dof.focal_distance - distance).abs() > FOCUS_TOLERANCE {
+        dof.focal_distance = distance;
+    }
+}
@@ -0,0 +1,141 @@
+//! Volumes placed in levels that change the camera while the tracked entity is inside them.
+
+use std::time::Duration;
+
+use avian3d::prelude::CollidingEntities;
+use bevy::prelude::*;
+use bevy_dolly::prelude::{LookAt, Position, Rig};
+use serde::Deserialize;
+
+use super::mode::{CameraMode, CameraModes, CameraRig, SetCameraMode};
+use crate::{game::spawn::player::CameraTracked, screen::Screen, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<FixedCameraTarget>().add_systems(
+        Update,
+        (activate_camera_volumes, aim_fixed_camera)
+            .chain()
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// The camera to use inside a [`CameraVolume`].
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum CameraShot {
+    Chase,
+    FirstPerson,
+    /// Watch from `eye`, looking at `target` or at the tracked entity if there's none.
+    Fixed {
+        eye: Vec3,
+        #[serde(default)]
+        target: Option<Vec3>,
+        /// Vertical field of view, in radians.
+        fov: f32,
+    },
+}
+
+/// A sensor that switches the camera to `shot` while the [`CameraTracked`] entity is inside it.
+#[derive(Component, Debug, Clone)]
+pub struct CameraVolume {
+    pub shot: CameraShot,
+    /// How long the transitions in and out of the volume take.
+    pub blend: Duration,
+}
+
+/// What the fixed camera looks at, if not the tracked entity.
+#[derive(Resource, Debug, Default)]
+struct FixedCameraTarget(Option<Vec3>);
+
+fn activate_camera_volumes(
+    mut commands: Commands,
+    modes: Res<CameraModes>,
+    // The volume that is currently in control of the camera.
+    mut current: Local<Option<Entity>>,
+    tracked: Query<Entity, With<CameraTracked>>,
+    volumes: Query<(Entity, &CameraVolume, &CollidingEntities)>,
+    mut rigs: Query<(&mut Rig, &mut CameraRig)>,
+    mut fixed_target: ResMut<FixedCameraTarget>,
+) {
+    // Don't get in the way of debugging.
+    if modes.active == CameraMode::FreeFly {
+        return;
+    }
+    let Ok(tracked) = tracked.get_single() else {
+        return;
+    };
+    // The volume may have been despawned along with its level, which resets the camera anyway.
+    if current.is_some_and(|volume| !volumes.contains(volume)) {
+        *current = None;
+    }
+
+    let inside = |volume| {
+        volumes
+            .get(volume)
+            .is_ok_and(|(_, _, colliding)| colliding.contains(&tracked))
+    };
+    // Stay in the current volume as long as possible, in case volumes overlap.
+    let next = current.filter(|volume| inside(*volume)).or_else(|| {
+        volumes
+            .iter()
+            .find(|(_, _, colliding)| colliding.contains(&tracked))
+            .map(|(volume, _, _)| volume)
+    });
+    if next == *current {
+        return;
+    }
+
+    match next.and_then(|volume| volumes.get(volume).ok()) {
+        Some((_, volume, _)) => {
+            let mode = match volume.shot {
+                CameraShot::Chase => CameraMode::Chase,
+                CameraShot::FirstPerson => CameraMode::FirstPerson,
+                CameraShot::Fixed { eye, target, fov } => {
+                    for (mut rig, mut camera_rig) in &mut rigs {
+                        if camera_rig.mode == CameraMode::Fixed {
+                            rig.driver_mut::<Position>().position = eye;
+                            camera_rig.fov = fov;
+                        }
+                    }
+                    fixed_target.0 = target;
+                    CameraMode::Fixed
+                }
+            };
+            commands.trigger(SetCameraMode {
+                mode,
+                blend: volume.blend,
+            });
+        }
+        None => {
+            // Go back to the player's camera, as slowly as we left it.
+            let blend = current
+                .and_then(|volume| volumes.get(volume).ok())
+                .map_or(Duration::ZERO, |(_, volume, _)| volume.blend);
+            commands.trigger(SetCameraMode {
+                mode: modes.preferred,
+                blend,
+            });
+        }
+    }
+    *current = next;
+}
+
+fn aim_fixed_camera(
+    target: Res<FixedCameraTarget>,
+    tracked: Query<&Transform, With<CameraTracked>>,
+    mut rigs: Query<(&mut Rig, &CameraRig)>,
+) {
+    let Some(target) = target.0.or_else(|| {
+        tracked
+            .get_single()
+            .ok()
+            .map(|transform| transform.translation)
+    }) else {
+        return;
+    };
+    for (mut rig, camera_rig) in &mut rigs {
+        if camera_rig.mode == CameraMode::Fixed {
+            rig.driver_mut::<LookAt>().target = target;
+        }
+    }
+}
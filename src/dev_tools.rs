@@ -1,13 +1,37 @@
 //! Development tools for the game. This plugin is only enabled in dev builds.
 
+use std::time::Duration;
+
 use avian3d::prelude::PhysicsDebugPlugin;
-use bevy::{dev_tools::states::log_transitions, prelude::*};
+use bevy::{
+    dev_tools::states::log_transitions, input::common_conditions::input_just_pressed, prelude::*,
+};
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
-use crate::screen::Screen;
+use crate::{
+    camera::mode::{CameraMode, CameraModes, SetCameraMode},
+    screen::Screen,
+};
 
 pub(super) fn plugin(app: &mut App) {
     // Print state transitions in dev builds
     app.add_plugins((WorldInspectorPlugin::new(), PhysicsDebugPlugin::default()))
-        .add_systems(Update, log_transitions::<Screen>);
+        .add_systems(Update, log_transitions::<Screen>)
+        .add_systems(
+            Update,
+            toggle_free_fly
+                .run_if(in_state(Screen::Playing).and_then(input_just_pressed(KeyCode::F2))),
+        );
+}
+
+/// Detach the camera from the player to fly around the level, or go back to the player's camera.
+fn toggle_free_fly(mut commands: Commands, modes: Res<CameraModes>) {
+    let mode = match modes.active {
+        CameraMode::FreeFly => modes.preferred,
+        _ => CameraMode::FreeFly,
+    };
+    commands.trigger(SetCameraMode {
+        mode,
+        blend: Duration::from_millis(500),
+    });
 }
@@ -0,0 +1,188 @@
+//! Accessories (glasses, canes, wheelchairs...) attached to characters.
+//!
+//! The available accessories are described in a RON file (`*.accessories.ron`) and loaded as an
+//! [`Accessories`] catalog. Adding an accessory to the [`Equipment`] of a character attaches its
+//! scene to the named node ("bone") of the character's scene, at spawn time or at any later point.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::HashMap,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{assets::CharactersAssets, level::LevelTransform};
+use crate::AppSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Accessories>()
+        .init_asset_loader::<AccessoriesLoader>()
+        .register_type::<Equipment>()
+        .add_systems(
+            Update,
+            attach_accessories
+                .after(AppSet::Update)
+                .run_if(resource_exists::<CharactersAssets>),
+        );
+}
+
+/// The accessories that can be equipped, by name.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct Accessories(pub HashMap<String, Accessory>);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Accessory {
+    /// Path of the glTF file, relative to the `assets` folder.
+    pub path: String,
+    /// Name of the node of the character's scene to attach to, e.g. `head`, `arm-right` or `root`
+    /// for the Kenney characters. The accessory is attached to the root of the character's model
+    /// if unset.
+    #[serde(default)]
+    pub bone: Option<String>,
+    /// Offset from the bone.
+    #[serde(default)]
+    pub transform: LevelTransform,
+    /// Handle to the first scene of the glTF file, loaded along with the catalog.
+    #[serde(skip)]
+    pub scene: Handle<Scene>,
+}
+
+/// The accessories a character wears or holds. Changing it attaches or detaches them.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct Equipment {
+    accessories: Vec<String>,
+    /// The entity spawned for each accessory, or `None` if it couldn't be.
+    #[reflect(ignore)]
+    attached: HashMap<String, Option<Entity>>,
+}
+
+impl Equipment {
+    pub fn new(accessories: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            accessories: accessories.into_iter().map(Into::into).collect(),
+            attached: HashMap::new(),
+        }
+    }
+
+    /// Replace the equipped accessories, keeping the ones that are already attached.
+    pub fn set(&mut self, accessories: impl IntoIterator<Item = impl Into<String>>) {
+        self.accessories = accessories.into_iter().map(Into::into).collect();
+    }
+}
+
+fn attach_accessories(
+    mut commands: Commands,
+    characters_assets: Res<CharactersAssets>,
+    catalogs: Res<Assets<Accessories>>,
+    mut characters: Query<(Entity, &mut Equipment)>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    scenes: Query<(), With<Handle<Scene>>>,
+) {
+    let Some(catalog) = catalogs.get(&characters_assets.accessories) else {
+        return;
+    };
+
+    for (character, mut equipment) in &mut characters {
+        // Only look at the equipment mutably when there's something to do, to keep change
+        // detection meaningful.
+        let is_dirty = equipment.accessories.len() != equipment.attached.len()
+            || equipment
+                .accessories
+                .iter()
+                .any(|a| !equipment.attached.contains_key(a));
+        if !is_dirty {
+            continue;
+        }
+        let equipment = equipment.bypass_change_detection();
+
+        // Detach what isn't equipped anymore.
+        let accessories = &equipment.accessories;
+        equipment.attached.retain(|name, entity| {
+            let keep = accessories.contains(name);
+            if let (false, Some(entity)) = (keep, *entity) {
+                commands.entity(entity).despawn_recursive();
+            }
+            keep
+        });
+
+        // Attach what is newly equipped.
+        for name in &equipment.accessories {
+            if equipment.attached.contains_key(name) {
+                continue;
+            }
+            let Some(accessory) = catalog.0.get(name) else {
+                warn!("Unknown accessory \"{name}\"");
+                equipment.attached.insert(name.clone(), None);
+                continue;
+            };
+            let parent = match &accessory.bone {
+                // The model is often a child of the character, offset to fit its collider.
+                None => std::iter::once(character)
+                    .chain(children.iter_descendants(character))
+                    .find(|entity| scenes.contains(*entity)),
+                // The character's scene may not be spawned yet, in which case this is retried
+                // next frame.
+                Some(bone) => children
+                    .iter_descendants(character)
+                    .find(|entity| names.get(*entity).is_ok_and(|n| n.as_str() == bone)),
+            };
+            let Some(parent) = parent else {
+                continue;
+            };
+            let entity = commands
+                .spawn((
+                    Name::new(format!("Accessory {name}")),
+                    SceneBundle {
+                        scene: accessory.scene.clone(),
+                        transform: (&accessory.transform).into(),
+                        ..default()
+                    },
+                ))
+                .set_parent(parent)
+                .id();
+            equipment.attached.insert(name.clone(), Some(entity));
+        }
+    }
+}
+
+/// Loads [`Accessories`] from `*.accessories.ron` files.
+#[derive(Default)]
+pub struct AccessoriesLoader;
+
+#[derive(Debug, Error)]
+pub enum AccessoriesLoaderError {
+    #[error("Could not read accessories file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse accessories file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for AccessoriesLoader {
+    type Asset = Accessories;
+    type Settings = ();
+    type Error = AccessoriesLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut accessories: Accessories = ron::de::from_bytes(&bytes)?;
+        // Load the scenes as dependencies of the catalog, so they're ready when it is.
+        for accessory in accessories.0.values_mut() {
+            accessory.scene =
+                load_context.load(GltfAssetLabel::Scene(0).from_asset(accessory.path.clone()));
+        }
+        Ok(accessories)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["accessories.ron"]
+    }
+}
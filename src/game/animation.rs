@@ -0,0 +1,350 @@
+//! Data-driven animation state machines.
+//!
+//! The states of a character, the clip each one plays and how to blend between them are described
+//! in RON files (`*.anim.ron`) under `assets/animations` and loaded as [`AnimationStates`]. They
+//! are resolved against the animations of a glTF file into an [`AnimationStateMachine`], which
+//! gameplay code drives with [`AnimationStateMachine::set_state`].
+//!
+//! States can also mark points of their clip, like the moments a foot touches the ground, which
+//! trigger an [`AnimationEvent`] on the entity of the state machine when the clip plays through
+//! them.
+
+use std::time::Duration;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::HashMap,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::AppSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<AnimationStates>()
+        .init_asset_loader::<AnimationStatesLoader>()
+        .add_systems(
+            Update,
+            (
+                link_animation_players,
+                play_animations,
+                trigger_animation_events,
+            )
+                .chain()
+                .after(AppSet::Update),
+        );
+}
+
+/// The animation states of a character, as described by a `*.anim.ron` file.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct AnimationStates {
+    /// How long blending from one state to the next takes, in seconds, unless the state says
+    /// otherwise.
+    #[serde(default = "default_crossfade")]
+    pub crossfade: f32,
+    /// The state played instead of states that don't exist or whose clip is missing from the glTF
+    /// file, so that the character doesn't freeze in its bind pose.
+    #[serde(default = "default_fallback")]
+    pub fallback: String,
+    pub states: HashMap<String, AnimationStateDef>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnimationStateDef {
+    /// Name of the animation in the glTF file.
+    pub clip: String,
+    /// Whether the clip loops, or plays once and holds its last frame.
+    #[serde(default)]
+    pub repeat: bool,
+    /// Playback speed, multiplied by the speed given to [`AnimationStateMachine::set_state`].
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+    /// How long blending into this state takes, in seconds.
+    #[serde(default)]
+    pub crossfade: Option<f32>,
+    /// Events triggered while the clip plays.
+    #[serde(default)]
+    pub events: Vec<AnimationEventDef>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnimationEventDef {
+    /// When the event happens, as a fraction of the clip's duration.
+    pub at: f32,
+    pub name: String,
+}
+
+/// Triggered on the entity of an [`AnimationStateMachine`] when its clip reaches one of the events
+/// of the current state.
+#[derive(Event, Debug, Clone)]
+pub struct AnimationEvent {
+    pub name: String,
+}
+
+fn default_crossfade() -> f32 {
+    0.2
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+fn default_fallback() -> String {
+    "standing".to_string()
+}
+
+/// Plays the animations of an entity according to its current state.
+///
+/// The [`AnimationPlayer`] is usually spawned deeper in the hierarchy by a glTF scene, it's found
+/// automatically when it appears.
+#[derive(Component, Debug, Clone)]
+pub struct AnimationStateMachine {
+    graph: Handle<AnimationGraph>,
+    states: HashMap<String, AnimationState>,
+    state: Option<String>,
+    /// See [`AnimationStates::fallback`], unless its clip is missing too.
+    fallback: Option<String>,
+    speed: f32,
+    /// The state the animation player was last told to play.
+    playing: Option<String>,
+    /// How far into its clip the playing state was when events were last checked, as a fraction of
+    /// the clip's duration.
+    progress: Option<f32>,
+}
+
+#[derive(Debug, Clone)]
+struct AnimationState {
+    node: AnimationNodeIndex,
+    clip: Handle<AnimationClip>,
+    repeat: bool,
+    speed: f32,
+    crossfade: Duration,
+    events: Vec<AnimationEventDef>,
+}
+
+impl AnimationStateMachine {
+    /// Resolve the clips of `states` against the named animations of `gltf`, adding them to a new
+    /// animation graph. States whose clip doesn't exist are skipped with an error, and play the
+    /// fallback state instead.
+    pub fn new(states: &AnimationStates, gltf: &Gltf, graphs: &mut Assets<AnimationGraph>) -> Self {
+        let mut graph = AnimationGraph::new();
+        let mut resolved = HashMap::new();
+        for (name, state) in &states.states {
+            let Some(clip) = gltf.named_animations.get(state.clip.as_str()) else {
+                let mut available: Vec<_> = gltf.named_animations.keys().collect();
+                available.sort();
+                error!(
+                    "Animation state \"{name}\" uses missing clip \"{}\", available clips are {available:?}",
+                    state.clip
+                );
+                continue;
+            };
+            resolved.insert(
+                name.clone(),
+                AnimationState {
+                    node: graph.add_clip(clip.clone(), 1.0, graph.root),
+                    clip: clip.clone(),
+                    repeat: state.repeat,
+                    speed: state.speed,
+                    crossfade: Duration::from_secs_f32(state.crossfade.unwrap_or(states.crossfade)),
+                    events: state.events.clone(),
+                },
+            );
+        }
+
+        Self {
+            graph: graphs.add(graph),
+            fallback: resolved
+                .contains_key(&states.fallback)
+                .then(|| states.fallback.clone()),
+            states: resolved,
+            state: None,
+            speed: 1.0,
+            playing: None,
+            progress: None,
+        }
+    }
+
+    /// Blend into `state`, or just update the playback speed if it is already the current state.
+    pub fn set_state(&mut self, state: &str, speed: f32) {
+        if self.state.as_deref() != Some(state) {
+            if !self.states.contains_key(state) {
+                warn!("Unknown animation state \"{state}\", playing the fallback state instead");
+            }
+            self.state = Some(state.to_string());
+        }
+        self.speed = speed;
+    }
+}
+
+/// The entity holding the [`AnimationPlayer`] of an [`AnimationStateMachine`].
+#[derive(Component, Debug)]
+struct AnimationPlayerLink(Entity);
+
+fn link_animation_players(
+    mut commands: Commands,
+    players: Query<Entity, Added<AnimationPlayer>>,
+    parents: Query<&Parent>,
+    machines: Query<&AnimationStateMachine>,
+) {
+    for player in &players {
+        let Some((owner, machine)) = parents
+            .iter_ancestors(player)
+            .find_map(|ancestor| Some((ancestor, machines.get(ancestor).ok()?)))
+        else {
+            continue;
+        };
+        commands
+            .entity(player)
+            .insert((machine.graph.clone(), AnimationTransitions::new()));
+        commands.entity(owner).insert(AnimationPlayerLink(player));
+    }
+}
+
+fn play_animations(
+    mut machines: Query<
+        (&mut AnimationStateMachine, &AnimationPlayerLink),
+        Or<(Changed<AnimationStateMachine>, Added<AnimationPlayerLink>)>,
+    >,
+    mut players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+) {
+    for (mut machine, link) in &mut machines {
+        let Ok((mut player, mut transitions)) = players.get_mut(link.0) else {
+            continue;
+        };
+        // Don't trigger change detection for bookkeeping.
+        let machine = machine.bypass_change_detection();
+        let Some(name) = machine
+            .state
+            .as_ref()
+            .filter(|name| machine.states.contains_key(*name))
+            .or(machine.fallback.as_ref())
+        else {
+            continue;
+        };
+        let state = &machine.states[name];
+
+        if machine.playing.as_ref() != Some(name) {
+            let animation = transitions.play(&mut player, state.node, state.crossfade);
+            if state.repeat {
+                animation.repeat();
+            }
+            machine.playing = Some(name.clone());
+            machine.progress = None;
+        }
+        if let Some(animation) = player.animation_mut(state.node) {
+            animation.set_speed(state.speed * machine.speed);
+        }
+    }
+}
+
+fn trigger_animation_events(
+    mut commands: Commands,
+    mut machines: Query<(Entity, &mut AnimationStateMachine, &AnimationPlayerLink)>,
+    players: Query<&AnimationPlayer>,
+    clips: Res<Assets<AnimationClip>>,
+) {
+    for (entity, mut machine, link) in &mut machines {
+        let Some(state) = machine
+            .playing
+            .as_ref()
+            .and_then(|name| machine.states.get(name))
+        else {
+            continue;
+        };
+        if state.events.is_empty() {
+            continue;
+        }
+        let (Ok(player), Some(clip)) = (players.get(link.0), clips.get(&state.clip)) else {
+            continue;
+        };
+        let Some(animation) = player.animation(state.node) else {
+            continue;
+        };
+        let progress = animation.seek_time() / clip.duration().max(f32::EPSILON);
+        let previous = machine.progress;
+
+        // Events at the very start of the clip happen as soon as it starts.
+        let reached = |at: f32| match previous {
+            None => at <= progress,
+            Some(previous) if previous <= progress => previous < at && at <= progress,
+            // The clip looped.
+            Some(previous) => previous < at || at <= progress,
+        };
+        for event in state.events.iter().filter(|event| reached(event.at)) {
+            commands.trigger_targets(
+                AnimationEvent {
+                    name: event.name.clone(),
+                },
+                entity,
+            );
+        }
+        // Don't trigger change detection for bookkeeping.
+        machine.bypass_change_detection().progress = Some(progress);
+    }
+}
+
+impl AnimationStates {
+    /// Check the fallback state exists, and the crossfades, which would panic once turned into
+    /// [`Duration`]s.
+    fn validate(&self) -> Result<(), AnimationStatesLoaderError> {
+        if !self.states.contains_key(&self.fallback) {
+            return Err(AnimationStatesLoaderError::UnknownFallback(
+                self.fallback.clone(),
+            ));
+        }
+        let crossfades = std::iter::once(("the default".to_string(), self.crossfade)).chain(
+            self.states
+                .iter()
+                .filter_map(|(name, state)| Some((format!("\"{name}\""), state.crossfade?))),
+        );
+        for (name, crossfade) in crossfades {
+            if Duration::try_from_secs_f32(crossfade).is_err() {
+                return Err(AnimationStatesLoaderError::InvalidCrossfade(
+                    name, crossfade,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Loads [`AnimationStates`] from `*.anim.ron` files.
+#[derive(Default)]
+pub struct AnimationStatesLoader;
+
+#[derive(Debug, Error)]
+pub enum AnimationStatesLoaderError {
+    #[error("Could not read animation file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse animation file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Invalid crossfade duration for {0}: {1}")]
+    InvalidCrossfade(String, f32),
+    #[error("Unknown fallback state \"{0}\"")]
+    UnknownFallback(String),
+}
+
+impl AssetLoader for AnimationStatesLoader {
+    type Asset = AnimationStates;
+    type Settings = ();
+    type Error = AnimationStatesLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let states: AnimationStates = ron::de::from_bytes(&bytes)?;
+        states.validate()?;
+        Ok(states)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim.ron"]
+    }
+}
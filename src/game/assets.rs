@@ -1,11 +1,19 @@
 use std::ops::Index;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use bevy_asset_loader::{
     asset_collection::AssetCollection,
     loading_state::{config::ConfigureLoadingState, LoadingState, LoadingStateAppExt},
 };
+use serde::{Deserialize, Serialize};
 
+use super::{
+    accessory::Accessories,
+    audio::soundtrack::MusicLayer,
+    character::Character,
+    level::{Level, LevelLoader},
+    locomotion::LocomotionProfiles,
+};
 use crate::screen::Screen;
 
 pub(super) fn plugin(app: &mut App) {
@@ -14,19 +22,93 @@ pub(super) fn plugin(app: &mut App) {
     #[cfg(not(feature = "dev"))]
     let next_state = Screen::Title;
 
-    app.add_loading_state(
-        LoadingState::new(Screen::Loading)
-            .continue_to_state(next_state)
-            .load_collection::<CharactersAssets>()
-            // .load_collection::<PlayerAssets>()
-            .load_collection::<AudioAssets>(),
-    );
+    app.init_asset::<Level>()
+        .init_asset_loader::<LevelLoader>()
+        .add_loading_state(
+            LoadingState::new(Screen::Loading)
+                .continue_to_state(next_state)
+                .load_collection::<CharactersAssets>()
+                .load_collection::<LevelAssets>()
+                // .load_collection::<PlayerAssets>()
+                .load_collection::<AudioAssets>()
+                .init_resource::<SoundtrackStems>(),
+        );
 }
 
 #[derive(AssetCollection, Resource)]
 pub struct CharactersAssets {
+    #[asset(path = "kenney-characters/Models/GLB format/character-female-a.glb")]
+    pub female_a: Handle<Gltf>,
+    #[asset(path = "kenney-characters/Models/GLB format/character-female-b.glb")]
+    pub female_b: Handle<Gltf>,
+    #[asset(path = "kenney-characters/Models/GLB format/character-female-c.glb")]
+    pub female_c: Handle<Gltf>,
+    #[asset(path = "kenney-characters/Models/GLB format/character-female-d.glb")]
+    pub female_d: Handle<Gltf>,
+    #[asset(path = "kenney-characters/Models/GLB format/character-female-e.glb")]
+    pub female_e: Handle<Gltf>,
+    #[asset(path = "kenney-characters/Models/GLB format/character-female-f.glb")]
+    pub female_f: Handle<Gltf>,
     #[asset(path = "kenney-characters/Models/GLB format/character-male-a.glb")]
     pub male_a: Handle<Gltf>,
+    #[asset(path = "kenney-characters/Models/GLB format/character-male-b.glb")]
+    pub male_b: Handle<Gltf>,
+    #[asset(path = "kenney-characters/Models/GLB format/character-male-c.glb")]
+    pub male_c: Handle<Gltf>,
+    #[asset(path = "kenney-characters/Models/GLB format/character-male-d.glb")]
+    pub male_d: Handle<Gltf>,
+    #[asset(path = "kenney-characters/Models/GLB format/character-male-e.glb")]
+    pub male_e: Handle<Gltf>,
+    #[asset(path = "kenney-characters/Models/GLB format/character-male-f.glb")]
+    pub male_f: Handle<Gltf>,
+    #[asset(path = "locomotion/profiles.locomotion.ron")]
+    pub locomotion: Handle<LocomotionProfiles>,
+    #[asset(path = "accessories/kenney.accessories.ron")]
+    pub accessories: Handle<Accessories>,
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct LevelAssets {
+    /// The levels, in the order they're played.
+    #[asset(
+        paths("levels/playground.level.ron", "levels/stairs.level.ron"),
+        collection(typed)
+    )]
+    pub levels: Vec<Handle<Level>>,
+}
+
+/// Index of a level in [`LevelAssets::levels`]. Indices change when levels are added or
+/// reordered, so [`LevelKey`]s are persisted instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Reflect)]
+pub struct LevelId(pub usize);
+
+/// Stable identifier of a level: the path of its file.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct LevelKey(pub String);
+
+impl LevelAssets {
+    pub fn get(&self, id: LevelId) -> Option<&Handle<Level>> {
+        self.levels.get(id.0)
+    }
+
+    /// The level played after `id`, if it's not the last one.
+    pub fn next(&self, id: LevelId) -> Option<LevelId> {
+        let next = LevelId(id.0 + 1);
+        self.get(next).map(|_| next)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = LevelId> {
+        (0..self.levels.len()).map(LevelId)
+    }
+
+    pub fn key(&self, id: LevelId) -> Option<LevelKey> {
+        Some(LevelKey(self.get(id)?.path()?.to_string()))
+    }
+
+    /// The level with the given key, if it's still part of the game.
+    pub fn find(&self, key: &LevelKey) -> Option<LevelId> {
+        self.ids().find(|id| self.key(*id).as_ref() == Some(key))
+    }
 }
 
 // #[derive(AssetCollection, Resource)]
@@ -45,6 +127,13 @@ pub struct AudioAssets {
     pub credits_soundtrack: Handle<AudioSource>,
     #[asset(path = "audio/soundtracks/Fluffing A Duck.ogg")]
     pub gameplay_soundtrack: Handle<AudioSource>,
+    /// The stems of the soundtracks, at `audio/stems/<soundtrack>/<layer>.ogg`, see
+    /// [`SoundtrackStems`].
+    #[asset(
+        paths("audio/stems/gameplay/running.ogg", "audio/stems/gameplay/falling.ogg"),
+        collection(typed, mapped)
+    )]
+    pub stems: HashMap<String, Handle<AudioSource>>,
 
     // SFX
     #[asset(path = "audio/sfx/button_hover.ogg")]
@@ -59,6 +148,33 @@ pub struct AudioAssets {
     pub step3: Handle<AudioSource>,
     #[asset(path = "audio/sfx/step4.ogg")]
     pub step4: Handle<AudioSource>,
+    #[asset(path = "audio/sfx/pickup_coin.ogg")]
+    pub pickup_coin: Handle<AudioSource>,
+    #[asset(path = "audio/sfx/pickup_key.ogg")]
+    pub pickup_key: Handle<AudioSource>,
+    #[asset(path = "audio/sfx/pickup_boost.ogg")]
+    pub pickup_boost: Handle<AudioSource>,
+}
+
+impl Index<Character> for CharactersAssets {
+    type Output = Handle<Gltf>;
+
+    fn index(&self, index: Character) -> &Self::Output {
+        match index {
+            Character::FemaleA => &self.female_a,
+            Character::FemaleB => &self.female_b,
+            Character::FemaleC => &self.female_c,
+            Character::FemaleD => &self.female_d,
+            Character::FemaleE => &self.female_e,
+            Character::FemaleF => &self.female_f,
+            Character::MaleA => &self.male_a,
+            Character::MaleB => &self.male_b,
+            Character::MaleC => &self.male_c,
+            Character::MaleD => &self.male_d,
+            Character::MaleE => &self.male_e,
+            Character::MaleF => &self.male_f,
+        }
+    }
 }
 
 impl Index<SoundtrackKey> for AudioAssets {
@@ -72,6 +188,48 @@ impl Index<SoundtrackKey> for AudioAssets {
     }
 }
 
+/// The stems that play along with each soundtrack, each heard while its layer is active, sorted
+/// out from [`AudioAssets::stems`] once they're loaded.
+///
+/// Stems must be as long as their soundtrack to stay in sync when it loops.
+#[derive(Resource, Debug)]
+pub struct SoundtrackStems(HashMap<SoundtrackKey, Vec<(MusicLayer, Handle<AudioSource>)>>);
+
+impl SoundtrackStems {
+    pub fn get(&self, key: SoundtrackKey) -> &[(MusicLayer, Handle<AudioSource>)] {
+        self.0.get(&key).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl FromWorld for SoundtrackStems {
+    fn from_world(world: &mut World) -> Self {
+        let mut stems = HashMap::<_, Vec<_>>::new();
+        for (path, handle) in &world.resource::<AudioAssets>().stems {
+            let path = std::path::Path::new(path);
+            let soundtrack = path.parent().and_then(|dir| dir.file_name()?.to_str());
+            let layer = path.file_stem().and_then(|name| name.to_str());
+            let key = match soundtrack {
+                Some("credits") => SoundtrackKey::Credits,
+                Some("gameplay") => SoundtrackKey::Gameplay,
+                _ => {
+                    warn!("Stem {path:?} doesn't belong to a known soundtrack");
+                    continue;
+                }
+            };
+            let layer = match layer {
+                Some("running") => MusicLayer::Running,
+                Some("falling") => MusicLayer::Falling,
+                _ => {
+                    warn!("Stem {path:?} doesn't match a known music layer");
+                    continue;
+                }
+            };
+            stems.entry(key).or_default().push((layer, handle.clone()));
+        }
+        Self(stems)
+    }
+}
+
 impl Index<SfxKey> for AudioAssets {
     type Output = Handle<AudioSource>;
 
@@ -83,11 +241,14 @@ impl Index<SfxKey> for AudioAssets {
             SfxKey::Step2 => &self.step2,
             SfxKey::Step3 => &self.step3,
             SfxKey::Step4 => &self.step4,
+            SfxKey::PickupCoin => &self.pickup_coin,
+            SfxKey::PickupKey => &self.pickup_key,
+            SfxKey::PickupBoost => &self.pickup_boost,
         }
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Reflect, Deserialize)]
 pub enum SfxKey {
     ButtonHover,
     ButtonPress,
@@ -95,9 +256,12 @@ pub enum SfxKey {
     Step2,
     Step3,
     Step4,
+    PickupCoin,
+    PickupKey,
+    PickupBoost,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Reflect)]
 pub enum SoundtrackKey {
     Credits,
     Gameplay,
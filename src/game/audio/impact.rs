@@ -0,0 +1,85 @@
+//! Sounds of physics bodies hitting things.
+
+use std::time::Duration;
+
+use avian3d::prelude::{CollisionStarted, Collisions};
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+
+use super::sfx::{PlaySfx, Sfx};
+use crate::game::assets::SfxKey;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ImpactSound>()
+        .add_systems(Update, play_impact_sounds);
+}
+
+/// Plays a sound when the entity starts touching something, louder and lower the harder the
+/// impact.
+#[derive(Component, Debug, Clone, Deserialize, Reflect)]
+#[reflect(Component)]
+pub struct ImpactSound {
+    /// One of them is picked at random for each impact.
+    pub sounds: Vec<SfxKey>,
+    /// Impacts with a smaller impulse are silent, in newton-seconds.
+    #[serde(default)]
+    pub min_impulse: f32,
+    /// Impulse of an impact at full volume, in newton-seconds.
+    pub max_impulse: f32,
+    /// Minimum time between two sounds, in seconds, so that an object settling down doesn't
+    /// rattle.
+    #[serde(default = "default_cooldown")]
+    pub cooldown: f32,
+    /// When the last sound was played.
+    #[serde(skip)]
+    #[reflect(ignore)]
+    last_played: Option<Duration>,
+}
+
+fn default_cooldown() -> f32 {
+    0.15
+}
+
+fn play_impact_sounds(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut collision_events: EventReader<CollisionStarted>,
+    collisions: Res<Collisions>,
+    mut impact_sounds: Query<&mut ImpactSound>,
+) {
+    for CollisionStarted(entity1, entity2) in collision_events.read() {
+        // The contact may already be over if the body bounced off right away.
+        let Some(contacts) = collisions.get(*entity1, *entity2) else {
+            continue;
+        };
+        let impulse = contacts.total_normal_impulse;
+
+        for entity in [*entity1, *entity2] {
+            let Ok(mut impact_sound) = impact_sounds.get_mut(entity) else {
+                continue;
+            };
+            if impulse < impact_sound.min_impulse {
+                continue;
+            }
+            let now = time.elapsed();
+            if impact_sound.last_played.is_some_and(|last_played| {
+                (now - last_played).as_secs_f32() < impact_sound.cooldown
+            }) {
+                continue;
+            }
+            let Some(key) = impact_sound.sounds.choose(&mut rand::thread_rng()).copied() else {
+                continue;
+            };
+            impact_sound.last_played = Some(now);
+
+            let strength = (impulse / impact_sound.max_impulse).clamp(0.0, 1.0);
+            commands.trigger(
+                PlaySfx::new(Sfx::Key(key))
+                    .on(entity)
+                    .with_volume(strength)
+                    .with_speed(1.2 - 0.4 * strength),
+            );
+        }
+    }
+}
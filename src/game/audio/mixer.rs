@@ -0,0 +1,165 @@
+//! Volume of the different kinds of sounds, set by the player.
+//!
+//! Every sound plays on an [`AudioBus`], whose volume is multiplied by the master volume. Changing
+//! the volumes of [`AudioVolumes`] or of an [`AudioChannel`] applies to the sounds that are already
+//! playing too, and the volumes of the buses are saved for the next run.
+
+use bevy::{
+    audio::{AudioSinkPlayback, SpatialAudioSink, Volume},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(AudioVolumes::load())
+        .register_type::<AudioBus>()
+        .register_type::<AudioChannel>()
+        .register_type::<AudioVolumes>()
+        .add_systems(
+            Update,
+            (
+                update_sink_volumes,
+                save_volumes.run_if(resource_changed::<AudioVolumes>),
+            ),
+        );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum AudioBus {
+    /// All the other buses go through this one.
+    Master,
+    Music,
+    Sfx,
+    Ui,
+}
+
+impl AudioBus {
+    pub const ALL: [AudioBus; 4] = [
+        AudioBus::Master,
+        AudioBus::Music,
+        AudioBus::Sfx,
+        AudioBus::Ui,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AudioBus::Master => "Master",
+            AudioBus::Music => "Music",
+            AudioBus::Sfx => "Effects",
+            AudioBus::Ui => "Interface",
+        }
+    }
+}
+
+/// The bus a sound plays on, and its own volume before mixing.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct AudioChannel {
+    pub bus: AudioBus,
+    pub volume: f32,
+}
+
+impl AudioChannel {
+    pub fn new(bus: AudioBus, volume: f32) -> Self {
+        Self { bus, volume }
+    }
+
+    /// The volume to play the sound at, once mixed.
+    pub fn mixed_volume(&self, volumes: &AudioVolumes) -> Volume {
+        Volume::new(self.volume * volumes.mixed(self.bus))
+    }
+}
+
+/// Volume of each bus, from 0 to 1.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct AudioVolumes {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    pub ui: f32,
+}
+
+impl Default for AudioVolumes {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 0.8,
+            sfx: 1.0,
+            ui: 0.8,
+        }
+    }
+}
+
+impl AudioVolumes {
+    const FILE_NAME: &'static str = "audio";
+
+    fn load() -> Self {
+        storage::load(Self::FILE_NAME).unwrap_or_default()
+    }
+
+    /// Volume of `bus` on its own.
+    pub fn get(&self, bus: AudioBus) -> f32 {
+        match bus {
+            AudioBus::Master => self.master,
+            AudioBus::Music => self.music,
+            AudioBus::Sfx => self.sfx,
+            AudioBus::Ui => self.ui,
+        }
+    }
+
+    pub fn set(&mut self, bus: AudioBus, volume: f32) {
+        let volume = volume.clamp(0.0, 1.0);
+        match bus {
+            AudioBus::Master => self.master = volume,
+            AudioBus::Music => self.music = volume,
+            AudioBus::Sfx => self.sfx = volume,
+            AudioBus::Ui => self.ui = volume,
+        }
+    }
+
+    /// Volume of `bus` once it goes through the master bus.
+    pub fn mixed(&self, bus: AudioBus) -> f32 {
+        match bus {
+            AudioBus::Master => self.master,
+            bus => self.master * self.get(bus),
+        }
+    }
+}
+
+fn update_sink_volumes(
+    volumes: Res<AudioVolumes>,
+    global_volume: Res<GlobalVolume>,
+    sinks: Query<(
+        Ref<AudioChannel>,
+        Option<Ref<AudioSink>>,
+        Option<Ref<SpatialAudioSink>>,
+    )>,
+) {
+    for (channel, sink, spatial_sink) in &sinks {
+        let sink_added = sink.as_ref().is_some_and(Ref::is_added)
+            || spatial_sink.as_ref().is_some_and(Ref::is_added);
+        if !volumes.is_changed() && !channel.is_changed() && !sink_added {
+            continue;
+        }
+        // Sinks don't know about the global volume, unlike `PlaybackSettings`.
+        let volume = channel.mixed_volume(&volumes).get() * global_volume.volume.get();
+        if let Some(sink) = sink {
+            sink.set_volume(volume);
+        }
+        if let Some(sink) = spatial_sink {
+            sink.set_volume(volume);
+        }
+    }
+}
+
+fn save_volumes(volumes: Res<AudioVolumes>) {
+    // The resource is inserted at startup, there's nothing new to save.
+    if volumes.is_added() {
+        return;
+    }
+    storage::save(AudioVolumes::FILE_NAME, &*volumes);
+}
@@ -1,8 +1,15 @@
+pub mod impact;
+pub mod mixer;
 pub mod sfx;
 pub mod soundtrack;
 
 use bevy::prelude::*;
 
 pub fn plugin(app: &mut App) {
-    app.add_plugins((sfx::plugin, soundtrack::plugin));
+    app.add_plugins((
+        impact::plugin,
+        mixer::plugin,
+        sfx::plugin,
+        soundtrack::plugin,
+    ));
 }
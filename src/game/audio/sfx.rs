@@ -1,37 +1,170 @@
 use bevy::{audio::PlaybackMode, prelude::*};
 use rand::seq::SliceRandom;
 
-use crate::game::assets::{AudioAssets, SfxKey};
+use super::mixer::{AudioBus, AudioChannel, AudioVolumes};
+use crate::game::{
+    assets::{AudioAssets, SfxKey},
+    physics::Surface,
+    spawn::pickup::PickupKind,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.observe(play_sfx);
 }
 
-fn play_sfx(trigger: Trigger<PlaySfx>, mut commands: Commands, audio_assets: Res<AudioAssets>) {
-    let sfx_key = match trigger.event() {
-        PlaySfx::Key(key) => *key,
-        PlaySfx::RandomStep => random_step(),
+/// Fall speed at which landing sounds are the loudest, in meters per second.
+const MAX_LANDING_SPEED: f32 = 10.0;
+
+fn play_sfx(
+    trigger: Trigger<PlaySfx>,
+    mut commands: Commands,
+    audio_assets: Res<AudioAssets>,
+    volumes: Res<AudioVolumes>,
+) {
+    let event = trigger.event();
+    let (sfx_key, volume, speed) = match event.sound {
+        Sfx::Key(key) => (key, 1.0, 1.0),
+        Sfx::Pickup(kind) => match kind {
+            PickupKind::Coin => (SfxKey::PickupCoin, 0.8, 1.0),
+            PickupKind::Key => (SfxKey::PickupKey, 1.0, 1.0),
+            PickupKind::SpeedBoost => (SfxKey::PickupBoost, 0.8, 1.0),
+        },
+        Sfx::RandomStep(surface) => {
+            let (keys, volume, speed) = step_sounds(surface);
+            (random_key(keys), volume, speed)
+        }
+        Sfx::Landing {
+            surface,
+            fall_speed,
+        } => {
+            let (keys, volume, speed) = step_sounds(surface);
+            // A heavier, lower thud than a step.
+            let loudness = (fall_speed / MAX_LANDING_SPEED).clamp(0.2, 1.0);
+            (random_key(keys), volume * loudness, speed * 0.7)
+        }
     };
-    commands.spawn(AudioSourceBundle {
-        source: audio_assets[sfx_key].clone_weak(),
-        settings: PlaybackSettings {
-            mode: PlaybackMode::Despawn,
-            ..default()
+    let channel = AudioChannel::new(event.sound.bus(), volume * event.volume);
+    let mut sfx = commands.spawn((
+        AudioSourceBundle {
+            source: audio_assets[sfx_key].clone_weak(),
+            settings: PlaybackSettings {
+                mode: PlaybackMode::Despawn,
+                volume: channel.mixed_volume(&volumes),
+                speed: speed * event.speed,
+                spatial: event.emitter != SfxEmitter::Listener,
+                ..default()
+            },
         },
-    });
+        channel,
+    ));
+    match event.emitter {
+        SfxEmitter::Listener => {}
+        SfxEmitter::Position(position) => {
+            sfx.insert(TransformBundle::from_transform(
+                Transform::from_translation(position),
+            ));
+        }
+        // Follow the emitter around, and stop with it.
+        SfxEmitter::Entity(entity) => {
+            sfx.insert(TransformBundle::default()).set_parent(entity);
+        }
+    }
 }
 
 /// Trigger this event to play a single sound effect.
-#[derive(Event)]
-pub enum PlaySfx {
+#[derive(Event, Debug)]
+pub struct PlaySfx {
+    pub sound: Sfx,
+    pub emitter: SfxEmitter,
+    /// Multiplies the volume of the sound.
+    pub volume: f32,
+    /// Multiplies the playback speed, and so the pitch, of the sound.
+    pub speed: f32,
+}
+
+impl PlaySfx {
+    /// Play `sound` at the same volume wherever the listener is, e.g. for the UI.
+    pub fn new(sound: Sfx) -> Self {
+        Self {
+            sound,
+            emitter: SfxEmitter::Listener,
+            volume: 1.0,
+            speed: 1.0,
+        }
+    }
+
+    /// Play the sound from a point in the world.
+    pub fn at(self, position: Vec3) -> Self {
+        Self {
+            emitter: SfxEmitter::Position(position),
+            ..self
+        }
+    }
+
+    /// Play the sound from an entity, following it as it moves.
+    pub fn on(self, entity: Entity) -> Self {
+        Self {
+            emitter: SfxEmitter::Entity(entity),
+            ..self
+        }
+    }
+
+    pub fn with_volume(self, volume: f32) -> Self {
+        Self { volume, ..self }
+    }
+
+    pub fn with_speed(self, speed: f32) -> Self {
+        Self { speed, ..self }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Sfx {
     Key(SfxKey),
-    #[allow(dead_code)]
-    RandomStep,
+    /// Picking up an item of the given kind.
+    Pickup(PickupKind),
+    /// A footstep on the given surface.
+    RandomStep(Surface),
+    /// Landing on the given surface after falling at `fall_speed`, in meters per second.
+    Landing {
+        surface: Surface,
+        fall_speed: f32,
+    },
+}
+
+impl Sfx {
+    pub fn bus(&self) -> AudioBus {
+        match self {
+            Sfx::Key(SfxKey::ButtonHover | SfxKey::ButtonPress) => AudioBus::Ui,
+            _ => AudioBus::Sfx,
+        }
+    }
+}
+
+/// Where a sound effect comes from. Sounds played in the world get quieter with the distance to
+/// the [`SpatialListener`] of the main camera.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SfxEmitter {
+    /// Not played in the world.
+    Listener,
+    Position(Vec3),
+    Entity(Entity),
+}
+
+/// The step sounds of a surface, with their volume and playback speed.
+fn step_sounds(surface: Surface) -> (&'static [SfxKey], f32, f32) {
+    match surface {
+        Surface::Stone => (&[SfxKey::Step1, SfxKey::Step2], 1.0, 1.0),
+        Surface::Wood => (&[SfxKey::Step3, SfxKey::Step4], 1.0, 0.9),
+        Surface::Grass => (
+            &[SfxKey::Step1, SfxKey::Step2, SfxKey::Step3, SfxKey::Step4],
+            0.5,
+            0.8,
+        ),
+        Surface::Metal => (&[SfxKey::Step1, SfxKey::Step3], 1.0, 1.3),
+    }
 }
 
-fn random_step() -> SfxKey {
-    [SfxKey::Step1, SfxKey::Step2, SfxKey::Step3, SfxKey::Step4]
-        .choose(&mut rand::thread_rng())
-        .copied()
-        .unwrap()
+fn random_key(keys: &[SfxKey]) -> SfxKey {
+    keys.choose(&mut rand::thread_rng()).copied().unwrap()
 }
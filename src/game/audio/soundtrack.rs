@@ -1,48 +1,412 @@
-use bevy::{audio::PlaybackMode, prelude::*};
+use std::time::Duration;
 
-use crate::game::assets::{AudioAssets, SoundtrackKey};
+use bevy::{
+    audio::{AudioSinkPlayback, PlaybackMode},
+    ecs::system::EntityCommands,
+    prelude::*,
+    utils::HashSet,
+};
+use rand::seq::SliceRandom;
+
+use super::mixer::{AudioBus, AudioChannel, AudioVolumes};
+use crate::game::assets::{AudioAssets, SoundtrackKey, SoundtrackStems};
 
 pub(super) fn plugin(app: &mut App) {
-    app.register_type::<IsSoundtrack>();
+    app.init_resource::<SoundtrackCrossfade>();
+    app.init_resource::<ActiveMusicLayers>();
+    app.init_resource::<SoundtrackDucking>();
+    app.register_type::<(
+        IsSoundtrack,
+        SoundtrackLevel,
+        SoundtrackFade,
+        SoundtrackCrossfade,
+    )>();
+    app.register_type::<SoundtrackDucking>();
+    app.register_type::<(MusicLayer, SoundtrackStem, ActiveMusicLayers)>();
     app.observe(play_soundtrack);
+    app.add_systems(
+        Update,
+        (
+            advance_playlists,
+            (fade_soundtracks, fade_stems, duck_soundtracks),
+            update_soundtrack_volumes,
+        )
+            .chain(),
+    );
 }
 
 fn play_soundtrack(
     trigger: Trigger<PlaySoundtrack>,
     mut commands: Commands,
     audio_assets: Res<AudioAssets>,
-    soundtrack_query: Query<Entity, With<IsSoundtrack>>,
+    stems: Res<SoundtrackStems>,
+    volumes: Res<AudioVolumes>,
+    crossfade: Res<SoundtrackCrossfade>,
+    layers: Res<ActiveMusicLayers>,
+    soundtrack_query: Query<(Entity, &SoundtrackLevel), With<IsSoundtrack>>,
 ) {
-    for entity in &soundtrack_query {
-        commands.entity(entity).despawn_recursive();
+    // Fade out whatever is playing, from the volume it's at in case it was still fading in.
+    for (entity, level) in &soundtrack_query {
+        commands
+            .entity(entity)
+            .remove::<PlaylistPosition>()
+            .insert(SoundtrackFade::new(crossfade.0, level.0, 0.0, true));
+    }
+
+    match trigger.event() {
+        PlaySoundtrack::Key(key) => {
+            spawn_soundtrack(
+                &mut commands,
+                &audio_assets,
+                &stems,
+                &volumes,
+                &layers,
+                crossfade.0,
+                *key,
+                PlaybackMode::Loop,
+            );
+        }
+        PlaySoundtrack::Playlist(playlist) => {
+            let mut playlist = playlist.clone();
+            if playlist.order == PlaylistOrder::Shuffle {
+                playlist.tracks.shuffle(&mut rand::thread_rng());
+            }
+            let Some(&key) = playlist.tracks.first() else {
+                return;
+            };
+            spawn_soundtrack(
+                &mut commands,
+                &audio_assets,
+                &stems,
+                &volumes,
+                &layers,
+                crossfade.0,
+                key,
+                PlaybackMode::Once,
+            )
+            .insert(PlaylistPosition { playlist, index: 0 });
+        }
+        PlaySoundtrack::Disable => {}
     }
+}
 
-    let soundtrack_key = match trigger.event() {
-        PlaySoundtrack::Key(key) => *key,
-        PlaySoundtrack::Disable => return,
+/// Spawn a soundtrack that fades in, along with its stems.
+fn spawn_soundtrack<'a>(
+    commands: &'a mut Commands,
+    audio_assets: &AudioAssets,
+    stems: &SoundtrackStems,
+    volumes: &AudioVolumes,
+    layers: &ActiveMusicLayers,
+    fade: Duration,
+    key: SoundtrackKey,
+    mode: PlaybackMode,
+) -> EntityCommands<'a> {
+    let channel = AudioChannel::new(AudioBus::Music, 0.0);
+    let settings = PlaybackSettings {
+        mode,
+        volume: channel.mixed_volume(volumes),
+        ..default()
     };
-    commands.spawn((
+    let mut soundtrack = commands.spawn((
+        Name::new(format!("Soundtrack {key:?}")),
         AudioSourceBundle {
-            source: audio_assets[soundtrack_key].clone_weak(),
-            settings: PlaybackSettings {
-                mode: PlaybackMode::Loop,
-                ..default()
-            },
+            source: audio_assets[key].clone_weak(),
+            settings,
         },
+        channel,
+        SoundtrackLevel(0.0),
+        SoundtrackFade::new(fade, 0.0, 1.0, false),
         IsSoundtrack,
     ));
+    // Stems start on the same frame as the soundtrack so they stay in sync, and are despawned
+    // with it.
+    soundtrack.with_children(|children| {
+        for (layer, source) in stems.get(key) {
+            let layer = *layer;
+            children.spawn((
+                Name::new(format!("Soundtrack Stem {layer:?}")),
+                AudioSourceBundle {
+                    source: source.clone_weak(),
+                    settings,
+                },
+                channel,
+                SoundtrackStem {
+                    layer,
+                    gain: if layers.0.contains(&layer) { 1.0 } else { 0.0 },
+                },
+            ));
+        }
+    });
+    soundtrack
+}
+
+/// Start the next track of a playlist once the current one is over.
+fn advance_playlists(
+    mut commands: Commands,
+    audio_assets: Res<AudioAssets>,
+    stems: Res<SoundtrackStems>,
+    volumes: Res<AudioVolumes>,
+    crossfade: Res<SoundtrackCrossfade>,
+    layers: Res<ActiveMusicLayers>,
+    track_query: Query<(Entity, &AudioSink, &PlaylistPosition)>,
+) {
+    for (entity, sink, position) in &track_query {
+        if !sink.empty() {
+            continue;
+        }
+        commands.entity(entity).despawn_recursive();
+
+        let mut playlist = position.playlist.clone();
+        let mut index = position.index + 1;
+        if index >= playlist.tracks.len() {
+            index = 0;
+            if playlist.order == PlaylistOrder::Shuffle {
+                let last = playlist.tracks.last().copied();
+                playlist.tracks.shuffle(&mut rand::thread_rng());
+                // Don't play the same track twice in a row.
+                if playlist.tracks.first().copied() == last {
+                    let end = playlist.tracks.len() - 1;
+                    playlist.tracks.swap(0, end);
+                }
+            }
+        }
+        let key = playlist.tracks[index];
+        spawn_soundtrack(
+            &mut commands,
+            &audio_assets,
+            &stems,
+            &volumes,
+            &layers,
+            crossfade.0,
+            key,
+            PlaybackMode::Once,
+        )
+        .insert(PlaylistPosition { playlist, index });
+    }
+}
+
+fn fade_soundtracks(
+    mut commands: Commands,
+    // Fades shouldn't stop when the game is paused.
+    time: Res<Time<Real>>,
+    mut fade_query: Query<(Entity, &mut SoundtrackFade, &mut SoundtrackLevel)>,
+) {
+    for (entity, mut fade, mut level) in &mut fade_query {
+        fade.timer.tick(time.delta());
+        level.0 = fade.from.lerp(fade.to, fade.timer.fraction());
+        if !fade.timer.finished() {
+            continue;
+        }
+        if fade.despawn {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            commands.entity(entity).remove::<SoundtrackFade>();
+        }
+    }
+}
+
+/// Fade the stems in or out depending on which [`MusicLayer`]s are active.
+fn fade_stems(
+    time: Res<Time<Real>>,
+    layers: Res<ActiveMusicLayers>,
+    mut stem_query: Query<&mut SoundtrackStem>,
+) {
+    let step = time.delta_seconds() / STEM_FADE_SECS;
+    for mut stem in &mut stem_query {
+        let target = if layers.0.contains(&stem.layer) {
+            1.0
+        } else {
+            0.0
+        };
+        if stem.gain != target {
+            stem.gain = (stem.gain + step.copysign(target - stem.gain)).clamp(0.0, 1.0);
+        }
+    }
+}
+
+fn duck_soundtracks(time: Res<Time<Real>>, mut ducking: ResMut<SoundtrackDucking>) {
+    let target = if ducking.ducked { DUCKED_VOLUME } else { 1.0 };
+    if ducking.gain != target {
+        let step = time.delta_seconds() / DUCK_SECS;
+        ducking.gain =
+            (ducking.gain + step.copysign(target - ducking.gain)).clamp(DUCKED_VOLUME, 1.0);
+    }
+}
+
+/// Apply the fades and ducking to the soundtracks, which their stems follow.
+fn update_soundtrack_volumes(
+    ducking: Res<SoundtrackDucking>,
+    mut soundtrack_query: Query<(&SoundtrackLevel, &mut AudioChannel)>,
+    mut stem_query: Query<(&SoundtrackStem, &Parent, &mut AudioChannel), Without<SoundtrackLevel>>,
+) {
+    for (level, mut channel) in &mut soundtrack_query {
+        set_channel_volume(&mut channel, level.0 * ducking.gain);
+    }
+    for (stem, parent, mut channel) in &mut stem_query {
+        let Ok((soundtrack, _)) = soundtrack_query.get(parent.get()) else {
+            continue;
+        };
+        set_channel_volume(&mut channel, soundtrack.0 * ducking.gain * stem.gain);
+    }
+}
+
+/// Only touch the channel when needed, as changing it updates the sink.
+fn set_channel_volume(channel: &mut Mut<AudioChannel>, volume: f32) {
+    if channel.volume != volume {
+        channel.volume = volume;
+    }
 }
 
 /// Trigger this event to play or disable the soundtrack.
-/// Playing a new soundtrack will overwrite the previous one.
-/// Soundtracks will loop.
+/// Playing a new soundtrack crossfades from the previous one, for [`SoundtrackCrossfade`].
+/// Single soundtracks will loop.
 #[derive(Event)]
 pub enum PlaySoundtrack {
     Key(SoundtrackKey),
+    // Only the game plays playlists, and dev builds don't play its music.
+    #[cfg_attr(feature = "dev", allow(dead_code))]
+    Playlist(Playlist),
     Disable,
 }
 
+/// Several soundtracks played one after the other, over and over.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Playlist {
+    pub tracks: Vec<SoundtrackKey>,
+    pub order: PlaylistOrder,
+}
+
+#[cfg_attr(feature = "dev", allow(dead_code))]
+impl Playlist {
+    /// Play `tracks` in order.
+    pub fn new(tracks: impl IntoIterator<Item = SoundtrackKey>) -> Self {
+        Self {
+            tracks: tracks.into_iter().collect(),
+            order: PlaylistOrder::default(),
+        }
+    }
+
+    pub fn shuffled(self) -> Self {
+        Self {
+            order: PlaylistOrder::Shuffle,
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaylistOrder {
+    #[default]
+    Sequential,
+    /// Shuffled again every time the whole playlist has been played.
+    Shuffle,
+}
+
+/// How long it takes for a soundtrack to fade in, and the previous one to fade out.
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct SoundtrackCrossfade(pub Duration);
+
+impl Default for SoundtrackCrossfade {
+    fn default() -> Self {
+        Self(Duration::from_secs_f32(1.5))
+    }
+}
+
+/// Volume of the soundtrack when ducked, relative to its normal volume.
+const DUCKED_VOLUME: f32 = 0.3;
+/// How long it takes to duck the soundtrack, or bring it back, in seconds.
+const DUCK_SECS: f32 = 0.3;
+
+/// How long it takes for a stem to fade in or out when its layer changes, in seconds.
+const STEM_FADE_SECS: f32 = 1.0;
+
+/// A gameplay situation that brings in its own layer of the soundtrack, if it has a stem for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum MusicLayer {
+    /// The player is on the move.
+    Running,
+    /// The player is falling.
+    Falling,
+}
+
+/// The layers whose stems should be heard, set from the gameplay.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct ActiveMusicLayers(pub HashSet<MusicLayer>);
+
+impl ActiveMusicLayers {
+    pub fn set(&mut self, layer: MusicLayer, active: bool) {
+        if active {
+            self.0.insert(layer);
+        } else {
+            self.0.remove(&layer);
+        }
+    }
+}
+
+/// Lowers the volume of the soundtrack while `ducked`, e.g. when the game is paused.
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct SoundtrackDucking {
+    pub ducked: bool,
+    /// How much the soundtrack is ducked at the moment, moving towards its target.
+    gain: f32,
+}
+
+impl Default for SoundtrackDucking {
+    fn default() -> Self {
+        Self {
+            ducked: false,
+            gain: 1.0,
+        }
+    }
+}
+
 /// Marker component for the soundtrack entity so we can find it later.
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 struct IsSoundtrack;
+
+/// Volume of a soundtrack as it fades in or out, before ducking.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct SoundtrackLevel(f32);
+
+/// Where a soundtrack is in its playlist.
+#[derive(Component, Debug)]
+struct PlaylistPosition {
+    playlist: Playlist,
+    index: usize,
+}
+
+/// Changes the [`SoundtrackLevel`] of a soundtrack over time.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct SoundtrackFade {
+    timer: Timer,
+    from: f32,
+    to: f32,
+    /// Whether to despawn the soundtrack once the fade is over.
+    despawn: bool,
+}
+
+impl SoundtrackFade {
+    fn new(duration: Duration, from: f32, to: f32, despawn: bool) -> Self {
+        Self {
+            timer: Timer::new(duration, TimerMode::Once),
+            from,
+            to,
+            despawn,
+        }
+    }
+}
+
+/// A layer of a soundtrack, playing along with it as a child.
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+struct SoundtrackStem {
+    layer: MusicLayer,
+    /// Volume of the stem relative to its soundtrack, fading with its layer.
+    gain: f32,
+}
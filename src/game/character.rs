@@ -0,0 +1,94 @@
+//! The playable characters.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SelectedCharacter>()
+        .init_resource::<SelectedAccessories>()
+        .register_type::<Character>()
+        .register_type::<SelectedCharacter>()
+        .register_type::<SelectedAccessories>();
+}
+
+/// One of the characters of the Kenney character pack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect, Serialize, Deserialize)]
+pub enum Character {
+    FemaleA,
+    FemaleB,
+    FemaleC,
+    FemaleD,
+    FemaleE,
+    FemaleF,
+    #[default]
+    MaleA,
+    MaleB,
+    MaleC,
+    MaleD,
+    MaleE,
+    MaleF,
+}
+
+impl Character {
+    pub const ALL: [Character; 12] = [
+        Character::FemaleA,
+        Character::FemaleB,
+        Character::FemaleC,
+        Character::FemaleD,
+        Character::FemaleE,
+        Character::FemaleF,
+        Character::MaleA,
+        Character::MaleB,
+        Character::MaleC,
+        Character::MaleD,
+        Character::MaleE,
+        Character::MaleF,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Character::FemaleA => "Female A",
+            Character::FemaleB => "Female B",
+            Character::FemaleC => "Female C",
+            Character::FemaleD => "Female D",
+            Character::FemaleE => "Female E",
+            Character::FemaleF => "Female F",
+            Character::MaleA => "Male A",
+            Character::MaleB => "Male B",
+            Character::MaleC => "Male C",
+            Character::MaleD => "Male D",
+            Character::MaleE => "Male E",
+            Character::MaleF => "Male F",
+        }
+    }
+
+    /// The character `offset` places after this one, wrapping around.
+    pub fn cycle(self, offset: isize) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|c| *c == self)
+            .unwrap_or_default();
+        Self::ALL[(index as isize + offset).rem_euclid(Self::ALL.len() as isize) as usize]
+    }
+}
+
+/// The character the player plays as.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct SelectedCharacter(pub Character);
+
+/// The accessories the player's character wears, by name in the accessory catalog.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct SelectedAccessories(pub Vec<String>);
+
+impl SelectedAccessories {
+    /// Wear `accessory` if it isn't worn, take it off otherwise.
+    pub fn toggle(&mut self, accessory: &str) {
+        if let Some(index) = self.0.iter().position(|a| a == accessory) {
+            self.0.remove(index);
+        } else {
+            self.0.push(accessory.to_string());
+        }
+    }
+}
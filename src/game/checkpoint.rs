@@ -0,0 +1,154 @@
+//! Checkpoints that move the player's respawn point, and respawning when falling out of the level.
+
+use std::time::Duration;
+
+use avian3d::prelude::CollidingEntities;
+use bevy::prelude::*;
+
+use super::spawn::player::{Player, RespawnPlayer};
+use crate::{
+    screen::{PlayingState, Screen},
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<LastCheckpoint>()
+        .init_resource::<KillHeight>()
+        .register_type::<Checkpoint>()
+        .observe(start_respawn)
+        .add_systems(
+            Update,
+            (
+                tick_respawn_fade.in_set(AppSet::TickTimers),
+                (reach_checkpoints, detect_fall).in_set(AppSet::Update),
+            )
+                .run_if(in_state(PlayingState::Running)),
+        );
+}
+
+/// How long fading to black takes, and then fading back in.
+const RESPAWN_FADE: Duration = Duration::from_millis(300);
+
+/// A sensor that becomes the player's respawn point when they enter it.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Checkpoint {
+    /// Where the player respawns.
+    pub spawn: Transform,
+}
+
+/// Where the player respawns, set when the level is spawned and then by each [`Checkpoint`].
+#[derive(Resource, Debug, Default)]
+pub struct LastCheckpoint {
+    pub spawn: Transform,
+    /// The checkpoint that was reached last, if any.
+    pub checkpoint: Option<Entity>,
+}
+
+/// The player respawns when falling below this height.
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct KillHeight(pub f32);
+
+impl Default for KillHeight {
+    fn default() -> Self {
+        Self(f32::NEG_INFINITY)
+    }
+}
+
+/// Trigger this event to fade out, put the player back at the [`LastCheckpoint`] and fade in.
+#[derive(Event, Debug)]
+pub struct Respawn;
+
+/// The screen covering the game while respawning.
+#[derive(Component, Debug)]
+struct RespawnFade {
+    timer: Timer,
+    respawned: bool,
+}
+
+fn reach_checkpoints(
+    player: Query<Entity, With<Player>>,
+    checkpoints: Query<(Entity, &Checkpoint, &CollidingEntities)>,
+    mut last_checkpoint: ResMut<LastCheckpoint>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let Some((entity, checkpoint, _)) = checkpoints
+        .iter()
+        .find(|(_, _, colliding)| colliding.contains(&player))
+    else {
+        return;
+    };
+    if last_checkpoint.checkpoint != Some(entity) {
+        info!("Reached checkpoint {entity}");
+        *last_checkpoint = LastCheckpoint {
+            spawn: checkpoint.spawn,
+            checkpoint: Some(entity),
+        };
+    }
+}
+
+fn detect_fall(
+    mut commands: Commands,
+    kill_height: Res<KillHeight>,
+    player: Query<&Transform, With<Player>>,
+    fades: Query<(), With<RespawnFade>>,
+) {
+    let fallen = player
+        .iter()
+        .any(|transform| transform.translation.y < kill_height.0);
+    // Keep falling while the screen fades out.
+    if fallen && fades.is_empty() {
+        commands.trigger(Respawn);
+    }
+}
+
+fn start_respawn(_trigger: Trigger<Respawn>, mut commands: Commands) {
+    commands.spawn((
+        Name::new("Respawn fade"),
+        RespawnFade {
+            timer: Timer::new(RESPAWN_FADE * 2, TimerMode::Once),
+            respawned: false,
+        },
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: BackgroundColor(Color::BLACK.with_alpha(0.0)),
+            // Cover the rest of the UI too.
+            z_index: ZIndex::Global(i32::MAX),
+            ..default()
+        },
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn tick_respawn_fade(
+    mut commands: Commands,
+    time: Res<Time>,
+    last_checkpoint: Res<LastCheckpoint>,
+    mut fades: Query<(Entity, &mut RespawnFade, &mut BackgroundColor)>,
+) {
+    for (entity, mut fade, mut background) in &mut fades {
+        fade.timer.tick(time.delta());
+        let elapsed = fade.timer.elapsed();
+        // Move the player while the screen is black.
+        if !fade.respawned && elapsed >= RESPAWN_FADE {
+            commands.trigger(RespawnPlayer(last_checkpoint.spawn));
+            fade.respawned = true;
+        }
+        let alpha = if elapsed < RESPAWN_FADE {
+            elapsed.as_secs_f32() / RESPAWN_FADE.as_secs_f32()
+        } else {
+            fade.timer.remaining().as_secs_f32() / RESPAWN_FADE.as_secs_f32()
+        };
+        background.0 = Color::BLACK.with_alpha(alpha);
+        if fade.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Level goals, and completing the level once they're all met.
+
+use std::time::Duration;
+
+use avian3d::prelude::CollidingEntities;
+use bevy::{prelude::*, utils::HashMap};
+
+use super::spawn::{pickup::PickupKind, player::Player};
+use crate::{screen::PlayingState, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<LevelStats>()
+        .init_resource::<LevelTime>()
+        .register_type::<(GoalZone, LevelTime)>()
+        .add_systems(
+            Update,
+            (
+                tick_level_time.in_set(AppSet::TickTimers),
+                (reach_goal_zones, check_goals)
+                    .chain()
+                    .in_set(AppSet::Update),
+            )
+                .run_if(in_state(PlayingState::Running)),
+        );
+}
+
+/// A sensor the player has to enter to complete the level.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct GoalZone {
+    pub reached: bool,
+}
+
+/// How the player is doing in the current level, reset whenever a level is spawned.
+#[derive(Resource, Debug, Default)]
+pub struct LevelStats {
+    /// Names and kinds of the pickups collected so far.
+    pub collected: Vec<(String, PickupKind)>,
+    /// How many pickups of each kind the level has.
+    pub totals: HashMap<PickupKind, usize>,
+    /// How many coins must be collected, if that's one of the goals of the level.
+    pub coins_needed: Option<usize>,
+}
+
+impl LevelStats {
+    /// How many pickups of the given kind were collected.
+    pub fn collected(&self, kind: PickupKind) -> usize {
+        self.collected.iter().filter(|(_, k)| *k == kind).count()
+    }
+
+    /// How many pickups of the given kind the level has.
+    pub fn total(&self, kind: PickupKind) -> usize {
+        self.totals.get(&kind).copied().unwrap_or_default()
+    }
+}
+
+/// Time spent in the current level, pauses excluded, reset whenever a level is spawned.
+///
+/// Kept apart from [`LevelStats`], which would otherwise change every frame.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct LevelTime(pub Duration);
+
+/// Triggered when every goal of the current level has been met.
+#[derive(Event, Debug)]
+pub struct LevelCompleted;
+
+fn tick_level_time(time: Res<Time>, mut level_time: ResMut<LevelTime>) {
+    level_time.0 += time.delta();
+}
+
+fn reach_goal_zones(
+    player: Query<Entity, With<Player>>,
+    mut zones: Query<(&mut GoalZone, &CollidingEntities, &Name)>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    for (mut zone, colliding, name) in &mut zones {
+        if !zone.reached && colliding.contains(&player) {
+            info!("Reached {name}");
+            zone.reached = true;
+        }
+    }
+}
+
+fn check_goals(
+    mut commands: Commands,
+    stats: Res<LevelStats>,
+    level_time: Res<LevelTime>,
+    zones: Query<&GoalZone>,
+    mut next_state: ResMut<NextState<PlayingState>>,
+) {
+    // Nothing to complete.
+    if zones.is_empty() && stats.coins_needed.is_none() {
+        return;
+    }
+    let reached = zones.iter().all(|zone| zone.reached);
+    let collected = stats
+        .coins_needed
+        .is_none_or(|needed| stats.collected(PickupKind::Coin) >= needed);
+    if reached && collected {
+        info!("Level completed in {:?}", level_time.0);
+        commands.trigger(LevelCompleted);
+        next_state.set(PlayingState::Complete);
+    }
+}
@@ -0,0 +1,460 @@
+//! Data-driven level format.
+//!
+//! Levels are described in RON files (`*.level.ron`) under `assets/levels` and loaded as a
+//! [`Level`] asset. Spawning the content of a level is handled by
+//! [`SpawnLevel`](super::spawn::level::SpawnLevel).
+
+use std::time::Duration;
+
+use avian3d::prelude::{Collider, ColliderConstructor};
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::HashSet,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{audio::impact::ImpactSound, physics::Surface, spawn::pickup::PickupKind};
+use crate::camera::{physical::CameraPreset, volume::CameraShot};
+
+/// A level, as described by a `*.level.ron` file.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct Level {
+    /// Human readable name of the level.
+    pub name: String,
+    /// Where to place the camera when the level starts.
+    #[serde(default)]
+    pub camera: Option<LevelCamera>,
+    /// Exposure of the camera, depending on how bright the level is.
+    #[serde(default)]
+    pub camera_preset: CameraPreset,
+    /// Volumes that change the camera while the player is inside them.
+    #[serde(default)]
+    pub camera_volumes: Vec<LevelCameraVolume>,
+    /// Whether to show the infinite grid.
+    #[serde(default)]
+    pub grid: bool,
+    /// Where the player spawns.
+    pub player_spawn: LevelTransform,
+    /// Volumes that move the player's respawn point once they've been reached.
+    #[serde(default)]
+    pub checkpoints: Vec<LevelCheckpoint>,
+    /// The player respawns at the last checkpoint when falling below this height.
+    #[serde(default = "default_kill_height")]
+    pub kill_height: f32,
+    /// What the player has to do to complete the level. Levels without goals are never completed.
+    #[serde(default)]
+    pub goals: Vec<LevelGoal>,
+    /// Items the player can pick up.
+    #[serde(default)]
+    pub items: Vec<LevelItem>,
+    #[serde(default)]
+    pub lights: Vec<LevelLight>,
+    #[serde(default)]
+    pub bodies: Vec<LevelBody>,
+    #[serde(default)]
+    pub scenes: Vec<LevelScene>,
+}
+
+/// An establishing shot, which the camera blends from into the player's camera.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct LevelCamera {
+    pub eye: Vec3,
+    pub target: Vec3,
+    /// Vertical field of view, in radians.
+    pub fov: f32,
+    /// How long the transition to the player's camera takes, in seconds.
+    #[serde(default = "default_camera_blend")]
+    pub blend: f32,
+}
+
+/// A box that switches the camera to `shot` while the player is inside it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LevelCameraVolume {
+    pub name: String,
+    pub size: Vec3,
+    #[serde(default)]
+    pub transform: LevelTransform,
+    pub shot: CameraShot,
+    /// How long the transitions in and out of the volume take, in seconds.
+    #[serde(default = "default_camera_blend")]
+    pub blend: f32,
+}
+
+fn default_camera_blend() -> f32 {
+    1.0
+}
+
+/// A box that becomes the player's respawn point when they enter it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LevelCheckpoint {
+    pub name: String,
+    pub size: Vec3,
+    #[serde(default)]
+    pub transform: LevelTransform,
+    /// Where the player respawns, the center of the box if not set.
+    #[serde(default)]
+    pub spawn: Option<LevelTransform>,
+}
+
+/// Something the player has to do to complete the level, along with the other goals.
+#[derive(Debug, Clone, Deserialize)]
+pub enum LevelGoal {
+    /// Enter a box.
+    Reach {
+        name: String,
+        size: Vec3,
+        #[serde(default)]
+        transform: LevelTransform,
+    },
+    /// Collect at least `count` of the level's coins.
+    Collect { count: usize },
+}
+
+/// An item the player picks up by touching it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LevelItem {
+    pub name: String,
+    #[serde(default)]
+    pub kind: PickupKind,
+    #[serde(default)]
+    pub transform: LevelTransform,
+}
+
+fn default_kill_height() -> f32 {
+    -20.0
+}
+
+/// A transform in a designer-friendly form.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct LevelTransform {
+    pub translation: Vec3,
+    /// Euler angles (yaw, pitch, roll) in degrees.
+    pub rotation: Vec3,
+    pub scale: Vec3,
+    /// If set, overrides `rotation` so that the entity looks at this point.
+    pub looking_at: Option<Vec3>,
+}
+
+impl Default for LevelTransform {
+    fn default() -> Self {
+        Self {
+            translation: Vec3::ZERO,
+            rotation: Vec3::ZERO,
+            scale: Vec3::ONE,
+            looking_at: None,
+        }
+    }
+}
+
+impl From<&LevelTransform> for Transform {
+    fn from(value: &LevelTransform) -> Self {
+        let transform = Transform {
+            translation: value.translation,
+            rotation: Quat::from_euler(
+                EulerRot::YXZ,
+                value.rotation.x.to_radians(),
+                value.rotation.y.to_radians(),
+                value.rotation.z.to_radians(),
+            ),
+            scale: value.scale,
+        };
+        match value.looking_at {
+            Some(target) => transform.looking_at(target, Vec3::Y),
+            None => transform,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum LevelLight {
+    Directional {
+        #[serde(default = "default_light_color")]
+        color: Color,
+        illuminance: f32,
+        #[serde(default)]
+        shadows: bool,
+        #[serde(default)]
+        transform: LevelTransform,
+    },
+    Point {
+        #[serde(default = "default_light_color")]
+        color: Color,
+        intensity: f32,
+        range: f32,
+        #[serde(default)]
+        shadows: bool,
+        #[serde(default)]
+        transform: LevelTransform,
+    },
+}
+
+fn default_light_color() -> Color {
+    Color::WHITE
+}
+
+/// A physics body with a primitive shape.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LevelBody {
+    pub name: String,
+    pub body: LevelBodyKind,
+    pub shape: LevelShape,
+    #[serde(default)]
+    pub material: LevelMaterial,
+    #[serde(default)]
+    pub transform: LevelTransform,
+    #[serde(default)]
+    pub restitution: Option<f32>,
+    #[serde(default)]
+    pub friction: Option<f32>,
+    /// What walking on the body sounds like.
+    #[serde(default)]
+    pub surface: Surface,
+    /// What the body sounds like when it hits something.
+    #[serde(default)]
+    pub impact_sound: Option<ImpactSound>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum LevelBodyKind {
+    Static,
+    Dynamic,
+    Kinematic,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum LevelShape {
+    /// An horizontal plane. Its collider is an infinite half-space.
+    Plane {
+        size: Vec2,
+    },
+    Cuboid {
+        size: Vec3,
+    },
+    Sphere {
+        radius: f32,
+    },
+    Capsule {
+        radius: f32,
+        length: f32,
+    },
+    Cylinder {
+        radius: f32,
+        height: f32,
+    },
+}
+
+impl LevelShape {
+    pub fn mesh(&self) -> Mesh {
+        match *self {
+            LevelShape::Plane { size } => Plane3d::default().mesh().size(size.x, size.y).into(),
+            LevelShape::Cuboid { size } => Cuboid::from_size(size).into(),
+            LevelShape::Sphere { radius } => Sphere::new(radius).into(),
+            LevelShape::Capsule { radius, length } => Capsule3d::new(radius, length).into(),
+            LevelShape::Cylinder { radius, height } => Cylinder::new(radius, height).into(),
+        }
+    }
+
+    pub fn collider(&self) -> Collider {
+        match *self {
+            LevelShape::Plane { .. } => Collider::half_space(Vec3::Y),
+            LevelShape::Cuboid { size } => Collider::cuboid(size.x, size.y, size.z),
+            LevelShape::Sphere { radius } => Collider::sphere(radius),
+            LevelShape::Capsule { radius, length } => Collider::capsule(radius, length),
+            LevelShape::Cylinder { radius, height } => Collider::cylinder(radius, height),
+        }
+    }
+}
+
+/// A glTF scene (e.g. authored in Blender) that is spawned as static level geometry.
+///
+/// Colliders are generated for every mesh of the scene using [`Self::collider`], unless a node
+/// overrides it, either with a name suffix (`-trimesh`, `-convex` or `-nocol`) or with a `collider`
+/// custom property (`"trimesh"`, `"convex_hull"` or `"none"`) exported as glTF extras.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LevelScene {
+    pub name: String,
+    /// Path of the glTF file, relative to the `assets` folder.
+    pub path: String,
+    #[serde(default)]
+    pub transform: LevelTransform,
+    #[serde(default)]
+    pub collider: SceneCollider,
+    /// What walking on the scene sounds like.
+    #[serde(default)]
+    pub surface: Surface,
+    /// Handle to the first scene of the glTF file, loaded along with the level.
+    #[serde(skip)]
+    pub scene: Handle<Scene>,
+}
+
+/// The kind of collider to generate for the meshes of a [`LevelScene`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum SceneCollider {
+    #[default]
+    Trimesh,
+    ConvexHull,
+    None,
+}
+
+impl SceneCollider {
+    /// Parses the collider kind from a node name suffix.
+    pub fn from_name_suffix(name: &str) -> Option<Self> {
+        if name.ends_with("-trimesh") {
+            Some(SceneCollider::Trimesh)
+        } else if name.ends_with("-convex") {
+            Some(SceneCollider::ConvexHull)
+        } else if name.ends_with("-nocol") {
+            Some(SceneCollider::None)
+        } else {
+            None
+        }
+    }
+
+    /// Parses the collider kind from the `collider` property of a glTF extras JSON object.
+    pub fn from_extras(extras: &str) -> Option<Self> {
+        #[derive(Deserialize)]
+        struct Extras {
+            collider: Option<String>,
+        }
+
+        let extras: Extras = serde_json::from_str(extras).ok()?;
+        match extras.collider?.as_str() {
+            "trimesh" => Some(SceneCollider::Trimesh),
+            "convex_hull" => Some(SceneCollider::ConvexHull),
+            "none" => Some(SceneCollider::None),
+            other => {
+                warn!("Unknown collider kind in glTF extras: {other}");
+                None
+            }
+        }
+    }
+
+    pub fn constructor(self) -> Option<ColliderConstructor> {
+        match self {
+            SceneCollider::Trimesh => Some(ColliderConstructor::TrimeshFromMesh),
+            SceneCollider::ConvexHull => Some(ColliderConstructor::ConvexHullFromMesh),
+            SceneCollider::None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct LevelMaterial {
+    pub color: Color,
+    pub perceptual_roughness: f32,
+    pub metallic: f32,
+}
+
+impl Default for LevelMaterial {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            perceptual_roughness: 0.5,
+            metallic: 0.0,
+        }
+    }
+}
+
+impl From<&LevelMaterial> for StandardMaterial {
+    fn from(value: &LevelMaterial) -> Self {
+        StandardMaterial {
+            base_color: value.color,
+            perceptual_roughness: value.perceptual_roughness,
+            metallic: value.metallic,
+            ..default()
+        }
+    }
+}
+
+impl Level {
+    /// Check the values that would only fail once the level is spawned.
+    fn validate(&self) -> Result<(), LevelLoaderError> {
+        let blends = self
+            .camera
+            .iter()
+            .map(|camera| ("the level camera".to_string(), camera.blend))
+            .chain(
+                self.camera_volumes
+                    .iter()
+                    .map(|volume| (volume.name.clone(), volume.blend)),
+            );
+        for (name, blend) in blends {
+            if Duration::try_from_secs_f32(blend).is_err() {
+                return Err(LevelLoaderError::InvalidBlend(name, blend));
+            }
+        }
+
+        let names: [(_, Vec<&String>); 3] = [
+            (
+                "bodies",
+                self.bodies.iter().map(|body| &body.name).collect(),
+            ),
+            (
+                "checkpoints",
+                self.checkpoints
+                    .iter()
+                    .map(|checkpoint| &checkpoint.name)
+                    .collect(),
+            ),
+            ("items", self.items.iter().map(|item| &item.name).collect()),
+        ];
+        for (kind, names) in names {
+            let mut seen = HashSet::new();
+            for name in names {
+                if !seen.insert(name) {
+                    return Err(LevelLoaderError::DuplicateName(kind, name.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Loads [`Level`]s from `*.level.ron` files.
+#[derive(Default)]
+pub struct LevelLoader;
+
+#[derive(Debug, Error)]
+pub enum LevelLoaderError {
+    #[error("Could not read level file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse level file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Invalid blend duration for {0}: {1}")]
+    InvalidBlend(String, f32),
+    /// Saved games find their bodies, items and checkpoints by name.
+    #[error("Several {0} are named \"{1}\"")]
+    DuplicateName(&'static str, String),
+}
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut level: Level = ron::de::from_bytes(&bytes)?;
+        level.validate()?;
+        // Load the scenes as dependencies of the level, so they're ready when the level is.
+        for scene in &mut level.scenes {
+            scene.scene =
+                load_context.load(GltfAssetLabel::Scene(0).from_asset(scene.path.clone()));
+        }
+        Ok(level)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
@@ -0,0 +1,154 @@
+//! Locomotion profiles: how the player's character gets around.
+//!
+//! Each profile is described in a RON file (`*.locomotion.ron`) loaded as [`LocomotionProfiles`],
+//! with the movement parameters, the abilities, the accessory (cane, wheelchair...) and the
+//! animation states that go with it.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::animation::AnimationStates;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<LocomotionProfiles>()
+        .init_asset_loader::<LocomotionProfilesLoader>()
+        .init_resource::<SelectedLocomotion>()
+        .register_type::<LocomotionProfile>()
+        .register_type::<SelectedLocomotion>();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect, Serialize, Deserialize)]
+pub enum LocomotionProfile {
+    #[default]
+    Walking,
+    Cane,
+    ManualWheelchair,
+    PowerWheelchair,
+}
+
+impl LocomotionProfile {
+    pub const ALL: [LocomotionProfile; 4] = [
+        LocomotionProfile::Walking,
+        LocomotionProfile::Cane,
+        LocomotionProfile::ManualWheelchair,
+        LocomotionProfile::PowerWheelchair,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LocomotionProfile::Walking => "Walking",
+            LocomotionProfile::Cane => "Cane",
+            LocomotionProfile::ManualWheelchair => "Manual wheelchair",
+            LocomotionProfile::PowerWheelchair => "Power wheelchair",
+        }
+    }
+
+    /// The profile `offset` places after this one, wrapping around.
+    pub fn cycle(self, offset: isize) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|p| *p == self)
+            .unwrap_or_default();
+        Self::ALL[(index as isize + offset).rem_euclid(Self::ALL.len() as isize) as usize]
+    }
+}
+
+/// How the player gets around.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct SelectedLocomotion(pub LocomotionProfile);
+
+/// The parameters of every [`LocomotionProfile`], as described by a `*.locomotion.ron` file.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct LocomotionProfiles {
+    pub walking: Locomotion,
+    pub cane: Locomotion,
+    pub manual_wheelchair: Locomotion,
+    pub power_wheelchair: Locomotion,
+}
+
+impl std::ops::Index<LocomotionProfile> for LocomotionProfiles {
+    type Output = Locomotion;
+
+    fn index(&self, index: LocomotionProfile) -> &Self::Output {
+        match index {
+            LocomotionProfile::Walking => &self.walking,
+            LocomotionProfile::Cane => &self.cane,
+            LocomotionProfile::ManualWheelchair => &self.manual_wheelchair,
+            LocomotionProfile::PowerWheelchair => &self.power_wheelchair,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Locomotion {
+    /// Top speed, in meters per second.
+    pub speed: f32,
+    /// How fast the top speed is reached, in meters per second squared.
+    pub acceleration: f32,
+    /// How much the character turns per frame when steering in tank controls, in radians.
+    pub angle_delta: f32,
+    /// How fast the character turns towards where it's going with camera-relative controls, in
+    /// radians per second.
+    pub turning_angvel: f32,
+    /// How high the character jumps, if it can.
+    #[serde(default)]
+    pub jump_height: Option<f32>,
+    #[serde(default)]
+    pub can_crouch: bool,
+    /// Accessory from the accessory catalog that comes with the profile.
+    #[serde(default)]
+    pub accessory: Option<String>,
+    /// Path of the animation states file, relative to the `assets` folder.
+    pub animations: String,
+    /// Handle to the animation states, loaded along with the profiles.
+    #[serde(skip)]
+    pub animation_states: Handle<AnimationStates>,
+}
+
+/// Loads [`LocomotionProfiles`] from `*.locomotion.ron` files.
+#[derive(Default)]
+pub struct LocomotionProfilesLoader;
+
+#[derive(Debug, Error)]
+pub enum LocomotionProfilesLoaderError {
+    #[error("Could not read locomotion file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse locomotion file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for LocomotionProfilesLoader {
+    type Asset = LocomotionProfiles;
+    type Settings = ();
+    type Error = LocomotionProfilesLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut profiles: LocomotionProfiles = ron::de::from_bytes(&bytes)?;
+        // Load the animations as dependencies of the profiles, so they're ready when they are.
+        for locomotion in [
+            &mut profiles.walking,
+            &mut profiles.cane,
+            &mut profiles.manual_wheelchair,
+            &mut profiles.power_wheelchair,
+        ] {
+            locomotion.animation_states = load_context.load(locomotion.animations.clone());
+        }
+        Ok(profiles)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["locomotion.ron"]
+    }
+}
@@ -2,10 +2,35 @@
 
 use bevy::prelude::*;
 
+pub mod accessory;
+pub mod animation;
 pub mod assets;
 pub mod audio;
+pub mod character;
+pub mod checkpoint;
+pub mod goal;
+pub mod level;
+pub mod locomotion;
+pub mod particles;
+pub mod physics;
+pub mod progress;
+pub mod save;
 pub mod spawn;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins((audio::plugin, assets::plugin, spawn::plugin));
+    app.add_plugins((
+        accessory::plugin,
+        animation::plugin,
+        audio::plugin,
+        character::plugin,
+        checkpoint::plugin,
+        goal::plugin,
+        locomotion::plugin,
+        particles::plugin,
+        physics::plugin,
+        progress::plugin,
+        save::plugin,
+        assets::plugin,
+        spawn::plugin,
+    ));
 }
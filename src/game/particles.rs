@@ -0,0 +1,107 @@
+//! Short bursts of particles, e.g. when something is picked up.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    screen::{PlayingState, Screen},
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ParticleMesh>()
+        .observe(spawn_particles)
+        .add_systems(
+            Update,
+            update_particles
+                .in_set(AppSet::Update)
+                .run_if(in_state(PlayingState::Running)),
+        );
+}
+
+const PARTICLE_LIFETIME: Duration = Duration::from_millis(600);
+/// Initial speed of the particles, in meters per second.
+const PARTICLE_SPEED: f32 = 3.0;
+const PARTICLE_GRAVITY: f32 = 9.81;
+
+/// Trigger this event to spawn a burst of particles.
+#[derive(Event, Debug)]
+pub struct SpawnParticles {
+    pub position: Vec3,
+    pub color: Color,
+    pub count: usize,
+}
+
+#[derive(Component, Debug)]
+struct Particle {
+    velocity: Vec3,
+    timer: Timer,
+}
+
+#[derive(Resource, Debug, Deref)]
+struct ParticleMesh(Handle<Mesh>);
+
+impl FromWorld for ParticleMesh {
+    fn from_world(world: &mut World) -> Self {
+        Self(world.resource_mut::<Assets<Mesh>>().add(Sphere::new(0.04)))
+    }
+}
+
+fn spawn_particles(
+    trigger: Trigger<SpawnParticles>,
+    mut commands: Commands,
+    mesh: Res<ParticleMesh>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let event = trigger.event();
+    let material = materials.add(StandardMaterial {
+        base_color: event.color,
+        emissive: event.color.to_linear() * 4.0,
+        unlit: true,
+        ..default()
+    });
+    let mut rng = rand::thread_rng();
+    for _ in 0..event.count {
+        // Mostly upwards, in every direction.
+        let direction = Vec3::new(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(0.2..1.0),
+            rng.gen_range(-1.0..1.0),
+        )
+        .normalize_or_zero();
+        commands.spawn((
+            Name::new("Particle"),
+            Particle {
+                velocity: direction * PARTICLE_SPEED * rng.gen_range(0.5..1.0),
+                timer: Timer::new(PARTICLE_LIFETIME, TimerMode::Once),
+            },
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                transform: Transform::from_translation(event.position),
+                ..default()
+            },
+            StateScoped(Screen::Playing),
+        ));
+    }
+}
+
+fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform)>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut particle, mut transform) in &mut particles {
+        if particle.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        particle.velocity.y -= PARTICLE_GRAVITY * dt;
+        transform.translation += particle.velocity * dt;
+        // Shrink away until the end.
+        transform.scale = Vec3::splat(particle.timer.fraction_remaining());
+    }
+}
@@ -0,0 +1,57 @@
+//! Physics layers and surfaces shared by the game's colliders.
+
+use avian3d::prelude::PhysicsLayer;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Surface>();
+}
+
+/// Colliders are in every layer unless told otherwise, so only special colliders need
+/// [`CollisionLayers`](avian3d::prelude::CollisionLayers).
+#[derive(Clone, Copy, Debug, Default)]
+pub enum GameLayer {
+    /// Solid geometry, which blocks characters and the camera.
+    #[default]
+    Default,
+    /// Sensor volumes, which detect characters but don't block anything.
+    Trigger,
+}
+
+// Implemented by hand: the derive macro checks for `2d`/`3d` features of our own crate, which
+// trips the `unexpected_cfgs` lint.
+impl PhysicsLayer for GameLayer {
+    fn to_bits(&self) -> u32 {
+        1 << *self as u32
+    }
+
+    fn all_bits() -> u32 {
+        0b11
+    }
+}
+
+/// What a collider is made of, which decides what walking on it sounds like.
+///
+/// It applies to the colliders of the entity's descendants too, so that a whole glTF scene can be
+/// given a surface.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect, Deserialize)]
+#[reflect(Component)]
+pub enum Surface {
+    #[default]
+    Stone,
+    Wood,
+    Grass,
+    Metal,
+}
+
+impl Surface {
+    /// The surface of `entity`, inherited from its closest ancestor that has one.
+    pub fn of(entity: Entity, surfaces: &Query<&Surface>, parents: &Query<&Parent>) -> Surface {
+        std::iter::once(entity)
+            .chain(parents.iter_ancestors(entity))
+            .find_map(|entity| surfaces.get(entity).ok())
+            .copied()
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,88 @@
+//! Which levels have been completed, saved to the `progress` file.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    assets::{LevelAssets, LevelKey},
+    goal::{LevelCompleted, LevelTime},
+    spawn::level::CurrentLevel,
+};
+use crate::storage;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(LevelProgress::load())
+        .observe(record_completion)
+        .add_systems(
+            Update,
+            save_progress.run_if(resource_changed::<LevelProgress>),
+        );
+}
+
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelProgress {
+    /// The best time of each completed level.
+    pub best_times: BTreeMap<LevelKey, Duration>,
+}
+
+impl LevelProgress {
+    const FILE_NAME: &'static str = "progress";
+
+    fn load() -> Self {
+        storage::load(Self::FILE_NAME).unwrap_or_default()
+    }
+
+    /// Whether the level played after `previous` is unlocked: the first level always is, the
+    /// others once the one before is completed.
+    pub fn is_unlocked(&self, previous: Option<&LevelKey>) -> bool {
+        previous.is_none_or(|previous| self.best_times.contains_key(previous))
+    }
+}
+
+fn record_completion(
+    _trigger: Trigger<LevelCompleted>,
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<LevelAssets>,
+    level_time: Res<LevelTime>,
+    mut progress: ResMut<LevelProgress>,
+) {
+    let Some(key) = level_assets.key(current_level.0) else {
+        return;
+    };
+    let best_time = progress.best_times.entry(key).or_insert(level_time.0);
+    *best_time = (*best_time).min(level_time.0);
+}
+
+fn save_progress(progress: Res<LevelProgress>) {
+    // The resource is inserted at startup, there's nothing new to save.
+    if progress.is_added() {
+        return;
+    }
+    storage::save(LevelProgress::FILE_NAME, &*progress);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_level_is_unlocked() {
+        assert!(LevelProgress::default().is_unlocked(None));
+    }
+
+    #[test]
+    fn levels_unlock_once_the_previous_one_is_completed() {
+        let first = LevelKey("levels/first.level.ron".to_string());
+        let mut progress = LevelProgress::default();
+        assert!(!progress.is_unlocked(Some(&first)));
+        progress
+            .best_times
+            .insert(first.clone(), Duration::from_secs(42));
+        assert!(progress.is_unlocked(Some(&first)));
+        let other = LevelKey("levels/other.level.ron".to_string());
+        assert!(!progress.is_unlocked(Some(&other)));
+    }
+}
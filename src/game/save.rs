@@ -0,0 +1,388 @@
+//! Save the game to slots and load it back.
+//!
+//! A save is stored as two files: a [`SaveHeader`] describing it, and a scene of the entities that
+//! move during the game (the player and the dynamic bodies of the level), extracted with
+//! reflection. Saved entities are found again by name when loading, once the level is spawned.
+
+use std::time::Duration;
+
+use avian3d::prelude::{AngularVelocity, LinearVelocity, RigidBody};
+use bevy::{
+    prelude::*,
+    scene::{serde::SceneDeserializer, DynamicSceneBuilder},
+    utils::HashMap,
+};
+use serde::{de::DeserializeSeed, Deserialize, Serialize};
+
+use super::{
+    assets::{LevelAssets, LevelKey},
+    character::{Character, SelectedAccessories, SelectedCharacter},
+    checkpoint::{Checkpoint, LastCheckpoint},
+    goal::{LevelStats, LevelTime},
+    level::Level,
+    locomotion::{LocomotionProfile, SelectedLocomotion},
+    spawn::{
+        level::{CurrentLevel, SelectedLevel},
+        pickup::{Pickup, PickupKind},
+        player::{PlayerAssets, PlayerParams},
+    },
+};
+use crate::{
+    screen::{PlayingState, Screen},
+    storage, AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PlayTime>()
+        .init_resource::<AutosaveTimer>()
+        .register_type::<PlayTime>()
+        .observe(save_game)
+        .observe(load_game)
+        .add_systems(OnEnter(Screen::Playing), start_play_time)
+        .add_systems(
+            Update,
+            (
+                (tick_play_time, autosave)
+                    .chain()
+                    .in_set(AppSet::TickTimers)
+                    .run_if(in_state(PlayingState::Running)),
+                apply_loaded_game
+                    .in_set(AppSet::Update)
+                    .run_if(in_state(Screen::Playing).and_then(resource_exists::<LoadedGame>)),
+            ),
+        );
+}
+
+/// Bump this when the format of the saves changes, older saves will be ignored.
+const SAVE_VERSION: u32 = 1;
+
+/// How often the game is saved to [`SaveSlot::Autosave`] while playing.
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum SaveSlot {
+    /// Written regularly while playing.
+    Autosave,
+    Manual(u8),
+}
+
+impl SaveSlot {
+    /// The slots the player can save to.
+    pub const MANUAL: [SaveSlot; 3] = [
+        SaveSlot::Manual(1),
+        SaveSlot::Manual(2),
+        SaveSlot::Manual(3),
+    ];
+    /// Every slot a game can be loaded from.
+    pub const ALL: [SaveSlot; 4] = [
+        SaveSlot::Autosave,
+        SaveSlot::Manual(1),
+        SaveSlot::Manual(2),
+        SaveSlot::Manual(3),
+    ];
+
+    pub fn name(self) -> String {
+        match self {
+            SaveSlot::Autosave => "Autosave".to_string(),
+            SaveSlot::Manual(index) => format!("Slot {index}"),
+        }
+    }
+
+    fn file_name(self) -> String {
+        match self {
+            SaveSlot::Autosave => "save-auto".to_string(),
+            SaveSlot::Manual(index) => format!("save-{index}"),
+        }
+    }
+
+    fn scene_file_name(self) -> String {
+        format!("{}-scene", self.file_name())
+    }
+
+    /// The header of the save in this slot, if there's one this version of the game can load.
+    pub fn header(self) -> Option<SaveHeader> {
+        let header = storage::load::<SaveHeader>(&self.file_name())?;
+        if header.version != SAVE_VERSION {
+            warn!(
+                "Ignoring {} saved with version {}",
+                self.name(),
+                header.version
+            );
+            return None;
+        }
+        Some(header)
+    }
+}
+
+/// Describes a save, and everything needed to set the game up before applying its scene.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveHeader {
+    pub version: u32,
+    pub level: LevelKey,
+    /// Human readable name of the level.
+    pub level_name: String,
+    pub play_time: Duration,
+    /// Time spent in the level.
+    pub level_time: Duration,
+    /// Names and kinds of the pickups collected in the level, to despawn them again.
+    pub collected: Vec<(String, PickupKind)>,
+    /// Name of the checkpoint the player respawns at, if they reached one.
+    pub checkpoint: Option<String>,
+    pub character: Character,
+    pub locomotion: LocomotionProfile,
+    pub accessories: Vec<String>,
+    /// Name of each entity of the scene, to find it again once the level is spawned.
+    pub entities: Vec<(Entity, String)>,
+}
+
+/// How long the current game has been played for, pauses excluded.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct PlayTime(pub Duration);
+
+#[derive(Resource, Debug, Deref, DerefMut)]
+struct AutosaveTimer(Timer);
+
+impl Default for AutosaveTimer {
+    fn default() -> Self {
+        Self(Timer::new(AUTOSAVE_INTERVAL, TimerMode::Repeating))
+    }
+}
+
+/// A save waiting to be applied to the level once it's spawned.
+#[derive(Resource)]
+pub struct LoadedGame {
+    pub header: SaveHeader,
+    scene: DynamicScene,
+}
+
+/// Trigger this event to save the game in the given slot.
+#[derive(Event, Debug)]
+pub struct SaveGame(pub SaveSlot);
+
+/// Trigger this event to start playing the game saved in the given slot.
+#[derive(Event, Debug)]
+pub struct LoadGame(pub SaveSlot);
+
+fn save_game(trigger: Trigger<SaveGame>, world: &World) {
+    let slot = trigger.event().0;
+    let Some(current_level) = world.get_resource::<CurrentLevel>() else {
+        warn!("There's no level to save");
+        return;
+    };
+    let level_assets = world.resource::<LevelAssets>();
+    let Some(level) = level_assets.key(current_level.0) else {
+        warn!("There's no level to save");
+        return;
+    };
+    let level_name = level_assets
+        .get(current_level.0)
+        .and_then(|handle| world.resource::<Assets<Level>>().get(handle))
+        .map(|level| level.name.clone())
+        .unwrap_or_default();
+    let stats = world.resource::<LevelStats>();
+    let checkpoint = world
+        .resource::<LastCheckpoint>()
+        .checkpoint
+        .and_then(|checkpoint| world.get::<Name>(checkpoint))
+        .map(|name| name.to_string());
+
+    // Static bodies don't move, there's no need to save them.
+    let entities = world
+        .iter_entities()
+        .filter(|entity| matches!(entity.get::<RigidBody>(), Some(RigidBody::Dynamic)))
+        .filter_map(|entity| Some((entity.id(), entity.get::<Name>()?.to_string())))
+        .collect::<Vec<_>>();
+    let scene = DynamicSceneBuilder::from_world(world)
+        .allow::<Transform>()
+        .allow::<LinearVelocity>()
+        .allow::<AngularVelocity>()
+        .allow::<PlayerParams>()
+        .extract_entities(entities.iter().map(|(entity, _)| *entity))
+        .build();
+    let scene = match scene.serialize(&world.resource::<AppTypeRegistry>().read()) {
+        Ok(scene) => scene,
+        Err(err) => {
+            error!("Could not serialize the game: {err}");
+            return;
+        }
+    };
+
+    let header = SaveHeader {
+        version: SAVE_VERSION,
+        level,
+        level_name,
+        play_time: world.resource::<PlayTime>().0,
+        level_time: world.resource::<LevelTime>().0,
+        collected: stats.collected.clone(),
+        checkpoint,
+        character: world.resource::<SelectedCharacter>().0,
+        locomotion: world.resource::<SelectedLocomotion>().0,
+        accessories: world.resource::<SelectedAccessories>().0.clone(),
+        entities,
+    };
+    storage::write(&slot.scene_file_name(), &scene);
+    storage::save(&slot.file_name(), &header);
+    info!("Saved the game to {}", slot.name());
+}
+
+fn load_game(
+    trigger: Trigger<LoadGame>,
+    mut commands: Commands,
+    registry: Res<AppTypeRegistry>,
+    mut selected_character: ResMut<SelectedCharacter>,
+    mut selected_locomotion: ResMut<SelectedLocomotion>,
+    mut selected_accessories: ResMut<SelectedAccessories>,
+    level_assets: Res<LevelAssets>,
+    mut selected_level: ResMut<SelectedLevel>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let slot = trigger.event().0;
+    let Some(header) = slot.header() else {
+        warn!("There's no game saved in {}", slot.name());
+        return;
+    };
+    let Some(level) = level_assets.find(&header.level) else {
+        warn!(
+            "The level of {} is not part of the game anymore",
+            slot.name()
+        );
+        return;
+    };
+    let Some(scene) = storage::read(&slot.scene_file_name()) else {
+        warn!("The scene of {} is missing", slot.name());
+        return;
+    };
+    let deserializer = SceneDeserializer {
+        type_registry: &registry.read(),
+    };
+    let scene = ron::de::Deserializer::from_str(&scene)
+        .map_err(ron::Error::from)
+        .and_then(|mut ron| deserializer.deserialize(&mut ron));
+    let scene = match scene {
+        Ok(scene) => scene,
+        Err(err) => {
+            error!("Could not read the scene of {}: {err}", slot.name());
+            return;
+        }
+    };
+
+    selected_character.0 = header.character;
+    selected_locomotion.0 = header.locomotion;
+    selected_accessories.0.clone_from(&header.accessories);
+    selected_level.0 = level;
+    commands.add(PlayerAssets::rebuild);
+    commands.insert_resource(LoadedGame { header, scene });
+    next_screen.set(Screen::Playing);
+}
+
+/// Put the saved entities back where they were, once the level has been spawned.
+fn apply_loaded_game(world: &mut World) {
+    let Some(LoadedGame { header, scene }) = world.remove_resource::<LoadedGame>() else {
+        return;
+    };
+
+    // Find every saved body before changing anything, so that a save which doesn't match the
+    // level anymore isn't half applied.
+    let saved_names = header.entities.into_iter().collect::<HashMap<_, _>>();
+    let bodies = world
+        .iter_entities()
+        .filter(|entity| matches!(entity.get::<RigidBody>(), Some(RigidBody::Dynamic)))
+        .filter_map(|entity| Some((entity.get::<Name>()?.to_string(), entity.id())))
+        .collect::<HashMap<_, _>>();
+    let mut targets = Vec::new();
+    for saved in &scene.entities {
+        let Some(target) = saved_names
+            .get(&saved.entity)
+            .and_then(|name| bodies.get(name))
+        else {
+            warn!(
+                "Could not find saved entity {:?} in the level, starting it over instead",
+                saved_names
+                    .get(&saved.entity)
+                    .unwrap_or(&saved.entity.to_string())
+            );
+            return;
+        };
+        targets.push((*target, saved));
+    }
+    let mut checkpoint = None;
+    if let Some(name) = &header.checkpoint {
+        let found = world.iter_entities().find_map(|entity| {
+            let spawn = entity.get::<Checkpoint>()?.spawn;
+            (entity.get::<Name>()?.as_str() == name).then_some((entity.id(), spawn))
+        });
+        let Some((entity, spawn)) = found else {
+            warn!(
+                "Could not find saved checkpoint {name:?} in the level, starting it over instead"
+            );
+            return;
+        };
+        checkpoint = Some(LastCheckpoint {
+            spawn,
+            checkpoint: Some(entity),
+        });
+    }
+
+    // The collected pickups were spawned along with the level.
+    let collected = world
+        .iter_entities()
+        .filter(|entity| entity.contains::<Pickup>())
+        .filter(|entity| {
+            entity.get::<Name>().is_some_and(|name| {
+                header
+                    .collected
+                    .iter()
+                    .any(|(item, _)| item == name.as_str())
+            })
+        })
+        .map(|entity| entity.id())
+        .collect::<Vec<_>>();
+    for entity in collected {
+        world.entity_mut(entity).despawn_recursive();
+    }
+    world.resource_mut::<LevelStats>().collected = header.collected;
+    world.insert_resource(LevelTime(header.level_time));
+    if let Some(checkpoint) = checkpoint {
+        world.insert_resource(checkpoint);
+    }
+
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    for (target, saved) in targets {
+        for component in &saved.components {
+            let reflect_component = component
+                .get_represented_type_info()
+                .and_then(|info| registry.get(info.type_id()))
+                .and_then(|registration| registration.data::<ReflectComponent>());
+            let Some(reflect_component) = reflect_component else {
+                warn!("Cannot load component {}", component.reflect_type_path());
+                continue;
+            };
+            reflect_component.apply_or_insert(
+                &mut world.entity_mut(target),
+                &**component,
+                &registry,
+            );
+        }
+    }
+}
+
+fn start_play_time(
+    loaded_game: Option<Res<LoadedGame>>,
+    mut play_time: ResMut<PlayTime>,
+    mut autosave_timer: ResMut<AutosaveTimer>,
+) {
+    play_time.0 = loaded_game.map_or(Duration::ZERO, |game| game.header.play_time);
+    autosave_timer.reset();
+}
+
+fn tick_play_time(time: Res<Time>, mut play_time: ResMut<PlayTime>) {
+    play_time.0 += time.delta();
+}
+
+fn autosave(mut commands: Commands, time: Res<Time>, mut timer: ResMut<AutosaveTimer>) {
+    if timer.tick(time.delta()).just_finished() {
+        commands.trigger(SaveGame(SaveSlot::Autosave));
+    }
+}
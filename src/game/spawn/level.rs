@@ -1,119 +1,328 @@
 //! Spawn the main level by triggering other observers.
 
-use avian3d::prelude::{Collider, ColliderConstructor, Restitution, RigidBody};
-use bevy::{color::palettes, math::vec3, pbr::DirectionalLightShadowMap, prelude::*};
+use std::time::Duration;
+
+use avian3d::prelude::{
+    Collider, CollidingEntities, CollisionLayers, Friction, LayerMask, Restitution, RigidBody,
+    Sensor,
+};
+use bevy::prelude::*;
 use bevy_infinite_grid::{InfiniteGridBundle, InfiniteGridPlugin};
 
-use crate::{camera::MainCamera, screen::Screen};
+use crate::{
+    camera::{
+        mode::{CameraModes, SetCameraMode},
+        physical::CameraParameters,
+        volume::CameraVolume,
+        MainCamera,
+    },
+    game::{
+        assets::{LevelAssets, LevelId},
+        checkpoint::{Checkpoint, KillHeight, LastCheckpoint},
+        goal::{GoalZone, LevelStats, LevelTime},
+        level::{Level, LevelBodyKind, LevelGoal, LevelLight},
+        physics::GameLayer,
+    },
+    screen::Screen,
+};
 
-use super::{player::SpawnPlayer, scene::SpawnScene};
+use super::{pickup::SpawnPickup, player::SpawnPlayer, scene::SpawnScene};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins(InfiniteGridPlugin)
-        .insert_resource(DirectionalLightShadowMap { size: 2048 })
         .insert_resource(AmbientLight {
             brightness: 100.0,
             ..default()
         })
-        .observe(spawn_level);
+        .init_resource::<SelectedLevel>()
+        .observe(spawn_level)
+        .observe(change_level)
+        .observe(restart_level);
+
+    // Respawn the level when its file changes on disk.
+    #[cfg(feature = "dev_native")]
+    app.add_systems(Update, reload_level.run_if(in_state(Screen::Playing)));
 }
 
+/// Trigger this event to spawn the given level.
+#[derive(Event, Debug)]
+pub struct SpawnLevel(pub LevelId);
+
+/// Trigger this event to despawn the current level and spawn the given one.
 #[derive(Event, Debug)]
-pub struct SpawnLevel;
+pub struct ChangeLevel(pub LevelId);
+
+/// Trigger this event to despawn the current level and spawn it again from the start.
+#[derive(Event, Debug)]
+pub struct RestartLevel;
+
+/// The level that was spawned last.
+#[derive(Resource, Debug)]
+pub struct CurrentLevel(pub LevelId);
+
+/// The level to spawn when the game starts.
+#[derive(Resource, Debug, Default)]
+pub struct SelectedLevel(pub LevelId);
 
 fn spawn_level(
-    _trigger: Trigger<SpawnLevel>,
+    trigger: Trigger<SpawnLevel>,
     mut commands: Commands,
+    level_assets: Res<LevelAssets>,
+    levels: Res<Assets<Level>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    camera_modes: Res<CameraModes>,
+    mut camera_params: ResMut<CameraParameters>,
     mut camera: Query<(&mut Transform, &mut Projection), With<MainCamera>>,
 ) {
-    // Add directional light
-    let transform = Transform::from_xyz(20.0, 10.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y);
-    commands.spawn((
-        DirectionalLightBundle {
-            transform,
-            directional_light: DirectionalLight {
-                illuminance: 4000.0,
-                shadows_enabled: true,
-                ..default()
+    let id = trigger.event().0;
+    let Some(level) = level_assets.get(id).and_then(|handle| levels.get(handle)) else {
+        error!("Level {id:?} is not loaded");
+        return;
+    };
+    info!("Spawning level {}", level.name);
+    commands.insert_resource(CurrentLevel(id));
+
+    // Add lights
+    for light in &level.lights {
+        match light {
+            LevelLight::Directional {
+                color,
+                illuminance,
+                shadows,
+                transform,
+            } => {
+                commands.spawn((
+                    DirectionalLightBundle {
+                        transform: transform.into(),
+                        directional_light: DirectionalLight {
+                            color: *color,
+                            illuminance: *illuminance,
+                            shadows_enabled: *shadows,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    Name::new("Sun"),
+                    StateScoped(Screen::Playing),
+                ));
+            }
+            LevelLight::Point {
+                color,
+                intensity,
+                range,
+                shadows,
+                transform,
+            } => {
+                commands.spawn((
+                    PointLightBundle {
+                        transform: transform.into(),
+                        point_light: PointLight {
+                            color: *color,
+                            intensity: *intensity,
+                            range: *range,
+                            shadows_enabled: *shadows,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    Name::new("Point light"),
+                    StateScoped(Screen::Playing),
+                ));
+            }
+        }
+    }
+
+    // Setup camera controller, starting from the establishing shot if there's one
+    let mut blend = Duration::ZERO;
+    if let Some(level_camera) = level.camera {
+        let (mut cam_transform, mut cam_proj) = camera.single_mut();
+        cam_transform.translation = level_camera.eye;
+        cam_transform.look_at(level_camera.target, Vec3::Y);
+        if let Projection::Perspective(ref mut proj) = *cam_proj {
+            proj.fov = level_camera.fov;
+        }
+        blend = Duration::from_secs_f32(level_camera.blend);
+    }
+    commands.trigger(SetCameraMode {
+        mode: camera_modes.preferred,
+        blend,
+    });
+    let params = camera_params.with_preset(level.camera_preset);
+    camera_params.set_if_neq(params);
+    for volume in &level.camera_volumes {
+        commands.spawn((
+            Name::new(volume.name.clone()),
+            CameraVolume {
+                shot: volume.shot,
+                blend: Duration::from_secs_f32(volume.blend),
             },
-            ..default()
-        },
-        Name::new("Sun"),
-        StateScoped(Screen::Playing),
-    ));
-
-    // Setup camera controller
-    let eye = vec3(0.0, 5.0, 15.0);
-    let target = Vec3::ZERO;
-    let (mut cam_transform, mut cam_proj) = camera.single_mut();
-    cam_transform.translation = eye;
-    cam_transform.look_at(target, Vec3::Y);
-    if let Projection::Perspective(ref mut proj) = *cam_proj {
-        proj.fov = 0.5;
+            TransformBundle::from_transform((&volume.transform).into()),
+            Collider::cuboid(volume.size.x, volume.size.y, volume.size.z),
+            Sensor,
+            CollisionLayers::new(GameLayer::Trigger, LayerMask::ALL),
+            CollidingEntities::default(),
+            StateScoped(Screen::Playing),
+        ));
     }
 
     // Infinite grid plane
-    commands.spawn((InfiniteGridBundle::default(), StateScoped(Screen::Playing)));
-
-    // Floor
-    commands.spawn((
-        Name::new("Floor"),
-        PbrBundle {
-            mesh: meshes.add(Plane3d::default().mesh().size(128.0, 128.0)),
-            material: materials.add(Color::WHITE),
-            ..default()
-        },
-        RigidBody::Static,
-        Collider::half_space(Vec3::Y),
-        StateScoped(Screen::Playing),
-    ));
-
-    // Platform
-    commands.spawn((
-        Name::new("Platform"),
-        PbrBundle {
-            mesh: meshes.add(Cuboid::new(5.0, 2.0, 5.0).mesh()),
-            material: materials.add(Color::from(palettes::basic::RED)),
-            transform: Transform::from_xyz(0.0, 1.0, 0.0),
-            ..default()
-        },
-        RigidBody::Static,
-        ColliderConstructor::default(),
-        StateScoped(Screen::Playing),
-    ));
-
-    // Box
-    commands.spawn((
-        Name::new("Box1"),
-        PbrBundle {
-            mesh: meshes.add(Cuboid::new(1.0, 1.0, 1.0).mesh()),
-            material: materials.add(Color::from(palettes::basic::PURPLE)),
-            transform: Transform::from_xyz(0.0, 2.5, 0.0),
-            ..default()
-        },
-        RigidBody::Dynamic,
-        ColliderConstructor::default(),
-        StateScoped(Screen::Playing),
-    ));
-
-    // Ball
-    commands.spawn((
-        Name::new("Ball1"),
-        PbrBundle {
-            mesh: meshes.add(Sphere::new(0.2).mesh()),
-            material: materials.add(Color::from(palettes::basic::PURPLE)),
-            transform: Transform::from_xyz(2.0, 2.5, 2.0),
-            ..default()
-        },
-        RigidBody::Dynamic,
-        // Make it a bit bouncy
-        Restitution::new(0.7),
-        ColliderConstructor::default(),
-        StateScoped(Screen::Playing),
-    ));
-
-    commands.trigger(SpawnScene);
-    commands.trigger(SpawnPlayer);
+    if level.grid {
+        commands.spawn((InfiniteGridBundle::default(), StateScoped(Screen::Playing)));
+    }
+
+    // Floor, platforms, props...
+    for body in &level.bodies {
+        let mut entity = commands.spawn((
+            Name::new(body.name.clone()),
+            PbrBundle {
+                mesh: meshes.add(body.shape.mesh()),
+                material: materials.add(StandardMaterial::from(&body.material)),
+                transform: (&body.transform).into(),
+                ..default()
+            },
+            match body.body {
+                LevelBodyKind::Static => RigidBody::Static,
+                LevelBodyKind::Dynamic => RigidBody::Dynamic,
+                LevelBodyKind::Kinematic => RigidBody::Kinematic,
+            },
+            body.shape.collider(),
+            body.surface,
+            StateScoped(Screen::Playing),
+        ));
+        if let Some(restitution) = body.restitution {
+            entity.insert(Restitution::new(restitution));
+        }
+        if let Some(friction) = body.friction {
+            entity.insert(Friction::new(friction));
+        }
+        if let Some(impact_sound) = &body.impact_sound {
+            entity.insert(impact_sound.clone());
+        }
+    }
+
+    // Static geometry from glTF scenes
+    for scene in &level.scenes {
+        commands.trigger(SpawnScene {
+            name: scene.name.clone(),
+            scene: scene.scene.clone(),
+            transform: (&scene.transform).into(),
+            collider: scene.collider,
+            surface: scene.surface,
+        });
+    }
+
+    // Checkpoints
+    let player_spawn = Transform::from(&level.player_spawn);
+    commands.insert_resource(LastCheckpoint {
+        spawn: player_spawn,
+        checkpoint: None,
+    });
+    commands.insert_resource(KillHeight(level.kill_height));
+    for checkpoint in &level.checkpoints {
+        let transform = Transform::from(&checkpoint.transform);
+        commands.spawn((
+            Name::new(checkpoint.name.clone()),
+            Checkpoint {
+                spawn: checkpoint
+                    .spawn
+                    .as_ref()
+                    .map_or(transform.with_scale(Vec3::ONE), Transform::from),
+            },
+            TransformBundle::from_transform(transform),
+            Collider::cuboid(checkpoint.size.x, checkpoint.size.y, checkpoint.size.z),
+            Sensor,
+            CollisionLayers::new(GameLayer::Trigger, LayerMask::ALL),
+            CollidingEntities::default(),
+            StateScoped(Screen::Playing),
+        ));
+    }
+
+    // Goals and pickups
+    let mut stats = LevelStats::default();
+    for goal in &level.goals {
+        match goal {
+            LevelGoal::Reach {
+                name,
+                size,
+                transform,
+            } => {
+                commands.spawn((
+                    Name::new(name.clone()),
+                    GoalZone::default(),
+                    TransformBundle::from_transform(transform.into()),
+                    Collider::cuboid(size.x, size.y, size.z),
+                    Sensor,
+                    CollisionLayers::new(GameLayer::Trigger, LayerMask::ALL),
+                    CollidingEntities::default(),
+                    StateScoped(Screen::Playing),
+                ));
+            }
+            LevelGoal::Collect { count } => {
+                stats.coins_needed = Some(stats.coins_needed.unwrap_or_default().max(*count));
+            }
+        }
+    }
+    for item in &level.items {
+        *stats.totals.entry(item.kind).or_default() += 1;
+        commands.trigger(SpawnPickup {
+            name: item.name.clone(),
+            kind: item.kind,
+            transform: (&item.transform).into(),
+        });
+    }
+    commands.insert_resource(stats);
+    commands.insert_resource(LevelTime::default());
+
+    commands.trigger(SpawnPlayer(player_spawn));
+}
+
+/// Despawn everything that belongs to the playing screen and spawn another level.
+fn change_level(
+    trigger: Trigger<ChangeLevel>,
+    mut commands: Commands,
+    scoped_entities: Query<(Entity, &StateScoped<Screen>)>,
+    parents: Query<&Parent>,
+) {
+    let is_playing = |entity| {
+        scoped_entities
+            .get(entity)
+            .is_ok_and(|(_, scope)| scope.0 == Screen::Playing)
+    };
+    for (entity, _) in &scoped_entities {
+        // Entities scoped below another one are despawned along with it.
+        if is_playing(entity) && !parents.iter_ancestors(entity).any(is_playing) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    commands.trigger(SpawnLevel(trigger.event().0));
+}
+
+fn restart_level(
+    _trigger: Trigger<RestartLevel>,
+    mut commands: Commands,
+    current_level: Option<Res<CurrentLevel>>,
+) {
+    if let Some(current_level) = current_level {
+        commands.trigger(ChangeLevel(current_level.0));
+    }
+}
+
+/// Restart the level if its file has been modified.
+#[cfg(feature = "dev_native")]
+fn reload_level(
+    mut commands: Commands,
+    mut asset_events: EventReader<AssetEvent<Level>>,
+    level_assets: Res<LevelAssets>,
+    current_level: Option<Res<CurrentLevel>>,
+) {
+    let Some(handle) = current_level.and_then(|level| level_assets.get(level.0)) else {
+        return;
+    };
+    if !asset_events.read().any(|event| event.is_modified(handle)) {
+        return;
+    }
+
+    info!("Level file changed, respawning level");
+    commands.trigger(RestartLevel);
 }
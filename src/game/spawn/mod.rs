@@ -5,9 +5,10 @@
 use bevy::prelude::*;
 
 pub mod level;
+pub mod pickup;
 pub mod player;
 pub mod scene;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins((level::plugin, player::plugin, scene::plugin));
+    app.add_plugins((level::plugin, pickup::plugin, player::plugin, scene::plugin));
 }
@@ -0,0 +1,234 @@
+//! Spawn the items the player picks up, and pick them up.
+
+use std::{f32::consts::TAU, time::Duration};
+
+use avian3d::prelude::{Collider, CollidingEntities, CollisionLayers, LayerMask, Sensor};
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use super::player::Player;
+use crate::{
+    game::{
+        audio::sfx::{PlaySfx, Sfx},
+        goal::LevelStats,
+        particles::SpawnParticles,
+        physics::GameLayer,
+    },
+    screen::{PlayingState, Screen},
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PickupAssets>()
+        .register_type::<Pickup>()
+        .register_type::<SpeedBoost>()
+        .observe(spawn_pickup)
+        .add_systems(
+            Update,
+            (
+                tick_speed_boost.in_set(AppSet::TickTimers),
+                (animate_pickups, collect_pickups).in_set(AppSet::Update),
+            )
+                .run_if(in_state(PlayingState::Running)),
+        );
+}
+
+/// Turns per second of the pickups.
+const SPIN_SPEED: f32 = 0.5;
+/// How far pickups move up and down, in meters.
+const BOB_HEIGHT: f32 = 0.1;
+/// Up and down movements per second of the pickups.
+const BOB_SPEED: f32 = 0.8;
+/// Radius of the sensor that detects the player.
+const PICKUP_RADIUS: f32 = 0.3;
+/// How much faster the player moves with a [`PickupKind::SpeedBoost`].
+const SPEED_BOOST_FACTOR: f32 = 1.6;
+const SPEED_BOOST_DURATION: Duration = Duration::from_secs(8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect, Serialize, Deserialize)]
+pub enum PickupKind {
+    /// Counts towards the [`LevelGoal::Collect`](crate::game::level::LevelGoal::Collect) goal.
+    #[default]
+    Coin,
+    Key,
+    /// Makes the player faster for a while.
+    SpeedBoost,
+}
+
+impl PickupKind {
+    /// The kinds of pickups the player keeps count of.
+    pub const COUNTED: [PickupKind; 2] = [PickupKind::Coin, PickupKind::Key];
+
+    pub fn color(self) -> Color {
+        match self {
+            PickupKind::Coin => Color::srgb(1.0, 0.8, 0.1),
+            PickupKind::Key => Color::srgb(0.75, 0.75, 0.8),
+            PickupKind::SpeedBoost => Color::srgb(0.2, 0.6, 1.0),
+        }
+    }
+}
+
+/// Trigger this event to spawn a pickup.
+#[derive(Event, Debug)]
+pub struct SpawnPickup {
+    pub name: String,
+    pub kind: PickupKind,
+    pub transform: Transform,
+}
+
+/// An item the player picks up by touching it.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    /// Where the pickup bobs around.
+    origin: Vec3,
+}
+
+/// The player moves faster until the timer finishes.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct SpeedBoost {
+    pub factor: f32,
+    timer: Timer,
+}
+
+impl SpeedBoost {
+    pub fn remaining(&self) -> Duration {
+        self.timer.remaining()
+    }
+
+    pub fn fraction_remaining(&self) -> f32 {
+        self.timer.fraction_remaining()
+    }
+}
+
+#[derive(Resource)]
+struct PickupAssets {
+    coin: Handle<Mesh>,
+    key: Handle<Mesh>,
+    speed_boost: Handle<Mesh>,
+    materials: HashMap<PickupKind, Handle<StandardMaterial>>,
+}
+
+impl FromWorld for PickupAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let coin = meshes.add(
+            Cylinder::new(0.25, 0.05)
+                .mesh()
+                .build()
+                .rotated_by(Quat::from_rotation_x(TAU / 4.0)),
+        );
+        let key = meshes.add(Cuboid::new(0.12, 0.4, 0.06));
+        let speed_boost = meshes.add(Sphere::new(0.2));
+
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let materials = [PickupKind::Coin, PickupKind::Key, PickupKind::SpeedBoost]
+            .into_iter()
+            .map(|kind| {
+                let material = materials.add(StandardMaterial {
+                    base_color: kind.color(),
+                    emissive: kind.color().to_linear() * 2.0,
+                    metallic: 0.8,
+                    ..default()
+                });
+                (kind, material)
+            })
+            .collect();
+
+        Self {
+            coin,
+            key,
+            speed_boost,
+            materials,
+        }
+    }
+}
+
+impl PickupAssets {
+    fn mesh(&self, kind: PickupKind) -> Handle<Mesh> {
+        match kind {
+            PickupKind::Coin => self.coin.clone(),
+            PickupKind::Key => self.key.clone(),
+            PickupKind::SpeedBoost => self.speed_boost.clone(),
+        }
+    }
+
+    fn material(&self, kind: PickupKind) -> Handle<StandardMaterial> {
+        self.materials[&kind].clone()
+    }
+}
+
+fn spawn_pickup(trigger: Trigger<SpawnPickup>, mut commands: Commands, assets: Res<PickupAssets>) {
+    let event = trigger.event();
+    commands.spawn((
+        Name::new(event.name.clone()),
+        Pickup {
+            kind: event.kind,
+            origin: event.transform.translation,
+        },
+        PbrBundle {
+            mesh: assets.mesh(event.kind),
+            material: assets.material(event.kind),
+            transform: event.transform,
+            ..default()
+        },
+        Collider::sphere(PICKUP_RADIUS),
+        Sensor,
+        CollisionLayers::new(GameLayer::Trigger, LayerMask::ALL),
+        CollidingEntities::default(),
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn animate_pickups(time: Res<Time>, mut pickups: Query<(&Pickup, &mut Transform)>) {
+    let elapsed = time.elapsed_seconds();
+    for (pickup, mut transform) in &mut pickups {
+        transform.rotate_y(SPIN_SPEED * TAU * time.delta_seconds());
+        transform.translation =
+            pickup.origin + Vec3::Y * BOB_HEIGHT * (BOB_SPEED * TAU * elapsed).sin();
+    }
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    player: Query<Entity, With<Player>>,
+    pickups: Query<(Entity, &Pickup, &Transform, &CollidingEntities, &Name)>,
+    mut stats: ResMut<LevelStats>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    for (entity, pickup, transform, colliding, name) in &pickups {
+        if !colliding.contains(&player) {
+            continue;
+        }
+        stats.collected.push((name.to_string(), pickup.kind));
+        if pickup.kind == PickupKind::SpeedBoost {
+            commands.entity(player).insert(SpeedBoost {
+                factor: SPEED_BOOST_FACTOR,
+                timer: Timer::new(SPEED_BOOST_DURATION, TimerMode::Once),
+            });
+        }
+        commands.trigger(PlaySfx::new(Sfx::Pickup(pickup.kind)).at(transform.translation));
+        commands.trigger(SpawnParticles {
+            position: transform.translation,
+            color: pickup.kind.color(),
+            count: 16,
+        });
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn tick_speed_boost(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut boosts: Query<(Entity, &mut SpeedBoost)>,
+) {
+    for (entity, mut boost) in &mut boosts {
+        if boost.timer.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<SpeedBoost>();
+        }
+    }
+}
@@ -1,38 +1,76 @@
 //! Spawn the player.
 
-use std::{collections::HashMap, f32::consts::PI};
+use std::f32::consts::PI;
 
-use avian3d::prelude::{Collider, DebugRender, LockedAxes, RigidBody};
+use avian3d::prelude::{
+    AngularVelocity, Collider, DebugRender, LinearVelocity, LockedAxes, RigidBody, SpatialQuery,
+    SpatialQueryFilter,
+};
 use bevy::{ecs::system::SystemState, prelude::*};
 use bevy_asset_loader::loading_state::{
     config::{ConfigureLoadingState, LoadingStateConfig},
     LoadingStateAppExt,
 };
-use bevy_dolly::prelude::{LookAt, Position, Rig, Rotation};
+use bevy_dolly::prelude::{Arm, LookAt, Position, Rig, Rotation};
 use bevy_tnua::{
     builtins::{TnuaBuiltinCrouch, TnuaBuiltinJumpState},
     prelude::{TnuaBuiltinJump, TnuaBuiltinWalk, TnuaController, TnuaControllerBundle},
     TnuaAction, TnuaAnimatingState, TnuaAnimatingStateDirective, TnuaUserControlsSystemSet,
 };
 use bevy_tnua_avian3d::TnuaAvian3dSensorShape;
+use serde::{Deserialize, Serialize};
 
-use crate::{game::assets::CharactersAssets, screen::Screen, AppSet};
+use crate::{
+    camera::{
+        mode::{CameraMode, CameraModes, CameraRig},
+        CameraOrbit, MainCamera,
+    },
+    game::{
+        accessory::Equipment,
+        animation::{AnimationEvent, AnimationStateMachine, AnimationStates},
+        assets::CharactersAssets,
+        audio::{
+            sfx::{PlaySfx, Sfx},
+            soundtrack::{ActiveMusicLayers, MusicLayer},
+        },
+        character::{SelectedAccessories, SelectedCharacter},
+        locomotion::{Locomotion, LocomotionProfiles, SelectedLocomotion},
+        physics::{GameLayer, Surface},
+        spawn::pickup::SpeedBoost,
+    },
+    input::{Action, ActionState},
+    screen::{PlayingState, Screen},
+    storage, AppSet,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.configure_loading_state(
         LoadingStateConfig::new(Screen::Loading).init_resource::<PlayerAssets>(),
     )
+    .insert_resource(MovementMode::load())
     .observe(spawn_player)
+    .observe(respawn_player)
+    .observe(play_footsteps)
+    .add_systems(
+        Update,
+        save_movement_mode.run_if(resource_changed::<MovementMode>),
+    )
     .add_systems(
         Update,
         (
-            apply_controls.in_set(TnuaUserControlsSystemSet),
-            prepare_animations.in_set(AppSet::Update),
-            handle_animations.in_set(AppSet::Update),
+            apply_controls
+                .in_set(TnuaUserControlsSystemSet)
+                .after(AppSet::RecordInput),
+            (handle_animations, update_music_layers)
+                .chain()
+                .in_set(AppSet::Update),
             move_camera.in_set(AppSet::Update),
+            apply_movement_mode.run_if(resource_changed::<MovementMode>),
         )
-            .run_if(in_state(Screen::Playing)),
+            .run_if(in_state(PlayingState::Running)),
     )
+    .add_systems(OnExit(Screen::Playing), clear_music_layers)
+    .register_type::<MovementMode>()
     .register_type::<PlayerParams>()
     .register_type::<Player>();
 }
@@ -40,23 +78,82 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Component)]
 pub struct CameraTracked;
 
+/// Height of the eyes above the center of the player, for the first-person camera.
+const EYE_HEIGHT: f32 = 0.6;
+
+/// Falls slower than this, in meters per second, land silently.
+const MIN_LANDING_SPEED: f32 = 2.0;
+
 #[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct PlayerParams {
     speed: f32,
+    acceleration: f32,
     angle_delta: f32,
     float_height: f32,
     cling_distance: f32,
     crouch_float_offset: f32,
+    movement_mode: MovementMode,
+    /// How fast the character turns towards where it's going in [`MovementMode::CameraRelative`],
+    /// in radians per second.
+    turning_angvel: f32,
+    /// How high the player jumps, if they can.
+    jump_height: Option<f32>,
+    can_crouch: bool,
+}
+
+/// How the movement actions are interpreted.
+///
+/// As a resource, it's the mode chosen by the player, which is given to the player's
+/// [`PlayerParams`] and saved to the `movement` file whenever it changes.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub enum MovementMode {
+    /// Forward/backward move along the character's forward direction, left/right rotate it. The
+    /// camera follows the character's rotation.
+    #[default]
+    Tank,
+    /// Movement is relative to the camera and the character turns towards where it's going. The
+    /// camera only rotates when orbited.
+    CameraRelative,
+}
+
+impl MovementMode {
+    const FILE_NAME: &'static str = "movement";
+
+    fn load() -> Self {
+        storage::load(Self::FILE_NAME).unwrap_or_default()
+    }
 }
 
+fn save_movement_mode(mode: Res<MovementMode>) {
+    // The resource is inserted at startup, there's nothing new to save.
+    if mode.is_added() {
+        return;
+    }
+    storage::save(MovementMode::FILE_NAME, &*mode);
+}
+
+/// Switch the player to the newly chosen mode without waiting for them to respawn.
+fn apply_movement_mode(mode: Res<MovementMode>, mut params: Query<&mut PlayerParams>) {
+    for mut params in &mut params {
+        params.movement_mode = *mode;
+    }
+}
+
+/// Trigger this event to spawn the player at the given position.
 #[derive(Event, Debug)]
-pub struct SpawnPlayer;
+pub struct SpawnPlayer(pub Transform);
+
+/// Trigger this event to move the player to the given position, as if they had just spawned.
+#[derive(Event, Debug)]
+pub struct RespawnPlayer(pub Transform);
 
 #[derive(Resource)]
 pub struct PlayerAssets {
     pub scene: Handle<Scene>,
-    pub graph: Handle<AnimationGraph>,
-    pub animations: HashMap<Box<str>, AnimationNodeIndex>,
+    pub animations: AnimationStateMachine,
+    pub locomotion: Locomotion,
 }
 
 impl FromWorld for PlayerAssets {
@@ -64,70 +161,137 @@ impl FromWorld for PlayerAssets {
         info!("Creating PlayerAnimations resource");
         let mut system_state = SystemState::<(
             Res<Assets<Gltf>>,
+            Res<Assets<AnimationStates>>,
             ResMut<Assets<AnimationGraph>>,
+            Res<Assets<LocomotionProfiles>>,
             Res<CharactersAssets>,
+            Res<SelectedCharacter>,
+            Res<SelectedLocomotion>,
         )>::new(world);
-        let (gltfs, mut graphs, characters_assets) = system_state.get_mut(world);
+        let (
+            gltfs,
+            animation_states,
+            mut graphs,
+            locomotion_profiles,
+            characters_assets,
+            selected_character,
+            selected_locomotion,
+        ) = system_state.get_mut(world);
 
-        let mut animation_ids: HashMap<Box<str>, AnimationNodeIndex> = HashMap::new();
-        let mut graph = AnimationGraph::new();
         let gltf = gltfs
-            .get(&characters_assets.male_a)
+            .get(&characters_assets[selected_character.0])
             .expect("Missing GLTF file!");
-        for (name, clip) in &gltf.named_animations {
-            let idx = graph.add_clip(clip.clone(), 1.0, graph.root);
-            animation_ids.insert(name.clone(), idx);
-        }
-
-        let graph = graphs.add(graph);
+        let locomotion = locomotion_profiles
+            .get(&characters_assets.locomotion)
+            .expect("Missing locomotion profiles file!")[selected_locomotion.0]
+            .clone();
+        let animation_states = animation_states
+            .get(&locomotion.animation_states)
+            .expect("Missing animation states file!");
+        let animations = AnimationStateMachine::new(animation_states, gltf, &mut graphs);
 
         let scene = gltf.scenes[0].clone();
 
         Self {
             scene,
-            graph,
-            animations: animation_ids,
+            animations,
+            locomotion,
         }
     }
 }
 
+impl PlayerAssets {
+    /// Build the assets again, e.g. after another character has been selected.
+    pub fn rebuild(world: &mut World) {
+        let assets = Self::from_world(world);
+        world.insert_resource(assets);
+    }
+}
+
+/// What the player is doing, as far as animations are concerned.
+///
+/// Also kept as a component on the player, so that other systems can react to it once
+/// `handle_animations` has updated it.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default)]
 pub enum PlayerAnimationState {
+    #[default]
     Standing,
     Running(f32),
     Jumping,
-    Falling,
+    /// Falling at the given speed, in meters per second.
+    Falling(f32),
     Crouch,
 }
 
+impl PlayerAnimationState {
+    /// Name of the state in the animation states file.
+    fn name(&self) -> &'static str {
+        match self {
+            PlayerAnimationState::Standing => "standing",
+            PlayerAnimationState::Running(_) => "running",
+            PlayerAnimationState::Jumping => "jumping",
+            PlayerAnimationState::Falling(_) => "falling",
+            PlayerAnimationState::Crouch => "crouching",
+        }
+    }
+
+    /// Playback speed of the animation.
+    fn speed(&self) -> f32 {
+        match self {
+            PlayerAnimationState::Running(speed) => *speed,
+            _ => 1.0,
+        }
+    }
+}
+
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
 #[reflect(Component)]
 pub struct Player;
 
 fn spawn_player(
-    _trigger: Trigger<SpawnPlayer>,
+    trigger: Trigger<SpawnPlayer>,
     player_assets: Res<PlayerAssets>,
+    movement_mode: Res<MovementMode>,
+    selected_accessories: Res<SelectedAccessories>,
     mut commands: Commands,
 ) {
     info!("Spawning player");
+    let locomotion = &player_assets.locomotion;
 
     commands
         .spawn((
             Name::new("Player"),
             Player,
             CameraTracked,
-            SpatialBundle::from_transform(Transform::from_xyz(0.0, 5.5, 0.0)),
+            SpatialBundle::from_transform(trigger.event().0),
             StateScoped(Screen::Playing),
-            TnuaAnimatingState::<PlayerAnimationState>::default(),
+            (
+                TnuaAnimatingState::<PlayerAnimationState>::default(),
+                PlayerAnimationState::default(),
+            ),
+            player_assets.animations.clone(),
+            Equipment::new(
+                locomotion
+                    .accessory
+                    .iter()
+                    .chain(&selected_accessories.0)
+                    .cloned(),
+            ),
             RigidBody::Dynamic,
             TnuaControllerBundle::default(),
             TnuaAvian3dSensorShape(Collider::cylinder(0.24, 0.0)),
             LockedAxes::ROTATION_LOCKED.unlock_rotation_y(),
             PlayerParams {
-                speed: 5.0,
-                angle_delta: 0.1,
+                speed: locomotion.speed,
+                acceleration: locomotion.acceleration,
+                angle_delta: locomotion.angle_delta,
                 float_height: 0.5,
                 cling_distance: 0.1,
                 crouch_float_offset: 0.0,
+                movement_mode: *movement_mode,
+                turning_angvel: locomotion.turning_angvel,
+                jump_height: locomotion.jump_height,
+                can_crouch: locomotion.can_crouch,
             },
             Collider::capsule(0.25, 0.1),
             DebugRender::all(),
@@ -144,20 +308,67 @@ fn spawn_player(
         });
 }
 
+fn respawn_player(
+    trigger: Trigger<RespawnPlayer>,
+    mut player_query: Query<
+        (
+            &mut Transform,
+            &mut LinearVelocity,
+            &mut AngularVelocity,
+            &mut TnuaController,
+            &mut TnuaAnimatingState<PlayerAnimationState>,
+            &mut AnimationStateMachine,
+        ),
+        With<Player>,
+    >,
+) {
+    let Ok((
+        mut transform,
+        mut linear_velocity,
+        mut angular_velocity,
+        mut controller,
+        mut animation_state,
+        mut animations,
+    )) = player_query.get_single_mut()
+    else {
+        return;
+    };
+    info!("Respawning player");
+    *transform = trigger.event().0;
+    linear_velocity.0 = Vec3::ZERO;
+    angular_velocity.0 = Vec3::ZERO;
+    // Forget about the jump or the fall that was going on.
+    *controller = TnuaController::default();
+    *animation_state = default();
+    let state = PlayerAnimationState::Standing;
+    animations.set_state(state.name(), state.speed());
+}
+
 fn handle_animations(
+    mut commands: Commands,
     mut player_query: Query<(
+        &Transform,
         &TnuaController,
+        &LinearVelocity,
         &mut TnuaAnimatingState<PlayerAnimationState>,
+        &mut PlayerAnimationState,
+        &mut AnimationStateMachine,
     )>,
-    mut animation_player_query: Query<&mut AnimationPlayer>,
-    player_assets: Res<PlayerAssets>,
+    surfaces: Query<&Surface>,
+    parents: Query<&Parent>,
 ) {
-    let Ok((controller, mut animation_state)) = player_query.get_single_mut() else {
-        return;
-    };
-    let Ok(mut animation_player) = animation_player_query.get_single_mut() else {
+    let Ok((
+        transform,
+        controller,
+        velocity,
+        mut animation_state,
+        mut current_state,
+        mut animations,
+    )) = player_query.get_single_mut()
+    else {
         return;
     };
+    let fall_speed = (-velocity.y).max(0.0);
 
     let current_status_for_animating = match controller.action_name() {
         Some(TnuaBuiltinJump::NAME) => {
@@ -175,7 +386,7 @@ fn handle_animations(
                 }
                 TnuaBuiltinJumpState::MaintainingJump => PlayerAnimationState::Jumping,
                 TnuaBuiltinJumpState::StoppedMaintainingJump => PlayerAnimationState::Jumping,
-                TnuaBuiltinJumpState::FallSection => PlayerAnimationState::Falling,
+                TnuaBuiltinJumpState::FallSection => PlayerAnimationState::Falling(fall_speed),
             }
         }
         Some(TnuaBuiltinCrouch::NAME) => PlayerAnimationState::Crouch,
@@ -186,7 +397,7 @@ fn handle_animations(
             };
             if basis_state.standing_on_entity().is_none() {
                 // Player isn't standing on an entity: it needs to fall
-                PlayerAnimationState::Falling
+                PlayerAnimationState::Falling(fall_speed)
             } else {
                 let speed = basis_state.running_velocity.length();
                 if 0.01 < speed {
@@ -198,100 +409,142 @@ fn handle_animations(
         }
     };
 
+    current_state.set_if_neq(current_status_for_animating);
     let animation_directive = animation_state.update_by_discriminant(current_status_for_animating);
     match animation_directive {
+        // We're staying in the same animation state, but the speed may change if we're running
         TnuaAnimatingStateDirective::Maintain { state } => {
-            // We're staying in the same animation state
-            // If we're running, adjust the speed though...
-            if let PlayerAnimationState::Running(speed) = state {
-                if let Some(animation) =
-                    animation_player.animation_mut(player_assets.animations["walk"])
-                {
-                    animation.set_speed(*speed);
-                }
+            if let PlayerAnimationState::Running(_) = state {
+                animations.set_state(state.name(), state.speed());
             }
         }
-        TnuaAnimatingStateDirective::Alter { state, .. } => {
-            animation_player.stop_all();
-            match state {
-                PlayerAnimationState::Standing => {
-                    animation_player.start(player_assets.animations["static"]);
-                    animation_player
-                        .start(player_assets.animations["idle"])
-                        .set_speed(1.0)
-                        .repeat();
-                }
-                PlayerAnimationState::Running(speed) => {
-                    animation_player
-                        .start(player_assets.animations["walk"])
-                        .set_speed(*speed)
-                        .repeat();
-                }
-                PlayerAnimationState::Falling => {
-                    animation_player
-                        .start(player_assets.animations["fall"])
-                        .set_speed(1.0)
-                        .repeat();
-                }
-                PlayerAnimationState::Jumping => {
-                    animation_player
-                        .start(player_assets.animations["jump"])
-                        .set_speed(1.0);
-                }
-                PlayerAnimationState::Crouch => {
-                    animation_player
-                        .start(player_assets.animations["crouch"])
-                        .set_speed(1.0);
+        TnuaAnimatingStateDirective::Alter { old_state, state } => {
+            animations.set_state(state.name(), state.speed());
+            // The speed of the last frame of the fall is the speed of the impact.
+            if let Some(PlayerAnimationState::Falling(fall_speed)) = old_state {
+                let ground = controller
+                    .concrete_basis::<TnuaBuiltinWalk>()
+                    .and_then(|(_, basis_state)| basis_state.standing_on_entity())
+                    .filter(|_| fall_speed > MIN_LANDING_SPEED);
+                if let Some(ground) = ground {
+                    let sound = Sfx::Landing {
+                        surface: Surface::of(ground, &surfaces, &parents),
+                        fall_speed,
+                    };
+                    // The thud comes from where the player landed, it doesn't follow them.
+                    commands.trigger(PlaySfx::new(sound).at(transform.translation));
                 }
             }
         }
     }
 }
 
-fn prepare_animations(
+/// Bring in the layers of the soundtrack that go with what the player is doing.
+fn update_music_layers(
+    player_query: Query<&PlayerAnimationState>,
+    mut layers: ResMut<ActiveMusicLayers>,
+) {
+    let Ok(state) = player_query.get_single() else {
+        return;
+    };
+    let running = matches!(state, PlayerAnimationState::Running(_));
+    let falling = matches!(state, PlayerAnimationState::Falling(_));
+    // Avoid triggering change detection every frame.
+    if layers.0.contains(&MusicLayer::Running) != running {
+        layers.set(MusicLayer::Running, running);
+    }
+    if layers.0.contains(&MusicLayer::Falling) != falling {
+        layers.set(MusicLayer::Falling, falling);
+    }
+}
+
+fn clear_music_layers(mut layers: ResMut<ActiveMusicLayers>) {
+    layers.0.clear();
+}
+
+/// Play a footstep when a foot of the walk animation touches the ground.
+fn play_footsteps(
+    trigger: Trigger<AnimationEvent>,
     mut commands: Commands,
-    player_assets: Res<PlayerAssets>,
-    players: Query<Entity, Added<AnimationPlayer>>,
+    controllers: Query<&TnuaController>,
+    surfaces: Query<&Surface>,
+    parents: Query<&Parent>,
 ) {
-    for entity in &players {
-        info!("Found AnimationPlayer for entity {entity}");
-        commands.entity(entity).insert(player_assets.graph.clone());
+    if trigger.event().name != "step" {
+        return;
     }
+    let Some(ground) = controllers
+        .get(trigger.entity())
+        .ok()
+        .and_then(|controller| controller.concrete_basis::<TnuaBuiltinWalk>())
+        .and_then(|(_, basis_state)| basis_state.standing_on_entity())
+    else {
+        return;
+    };
+    let sound = Sfx::RandomStep(Surface::of(ground, &surfaces, &parents));
+    commands.trigger(PlaySfx::new(sound).on(trigger.entity()));
 }
 
 fn apply_controls(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&mut TnuaController, &Transform, &PlayerParams)>,
+    actions: Res<ActionState>,
+    camera_modes: Res<CameraModes>,
+    mut query: Query<(
+        &mut TnuaController,
+        &Transform,
+        &PlayerParams,
+        Option<&SpeedBoost>,
+    )>,
+    camera: Query<&Transform, With<MainCamera>>,
 ) {
-    let Ok((mut controller, transform, player_params)) = query.get_single_mut() else {
+    let Ok((mut controller, transform, player_params, speed_boost)) = query.get_single_mut() else {
         return;
     };
 
-    let mut desired_velocity = Vec3::ZERO;
-    let mut desired_forward = transform.forward().as_vec3();
+    let mut walk = TnuaBuiltinWalk {
+        float_height: player_params.float_height,
+        cling_distance: player_params.cling_distance,
+        acceleration: player_params.acceleration,
+        ..default()
+    };
 
-    if keyboard.pressed(KeyCode::ArrowUp) {
-        desired_velocity = desired_forward;
-    } else if keyboard.pressed(KeyCode::ArrowDown) {
-        desired_velocity = -desired_forward;
-    }
-    if keyboard.pressed(KeyCode::ArrowLeft) {
-        desired_forward = Quat::from_rotation_y(player_params.angle_delta) * desired_forward;
-    } else if keyboard.pressed(KeyCode::ArrowRight) {
-        desired_forward = Quat::from_rotation_y(-player_params.angle_delta) * desired_forward;
+    // The movement actions fly the camera around instead.
+    if camera_modes.active == CameraMode::FreeFly {
+        controller.basis(walk);
+        return;
     }
 
+    let forward = transform.forward().as_vec3();
+    let desired_velocity = match player_params.movement_mode {
+        MovementMode::Tank => {
+            let turn = actions.axis(Action::TurnRight, Action::TurnLeft);
+            walk.desired_forward = (Quat::from_rotation_y(turn * player_params.angle_delta)
+                * forward)
+                .normalize_or_zero();
+            forward * actions.axis(Action::MoveBackward, Action::MoveForward)
+        }
+        MovementMode::CameraRelative => {
+            // Only the yaw of the camera matters.
+            let camera = camera.single();
+            let camera_forward = camera.forward().with_y(0.0).normalize_or_zero();
+            let camera_right = camera.right().with_y(0.0).normalize_or_zero();
+            let direction = camera_forward
+                * actions.axis(Action::MoveBackward, Action::MoveForward)
+                + camera_right * actions.axis(Action::TurnLeft, Action::TurnRight);
+            // Keep facing the same way when standing still.
+            walk.desired_forward = direction.try_normalize().unwrap_or(forward);
+            walk.turning_angvel = player_params.turning_angvel;
+            direction
+        }
+    };
+    // Analog sticks may not be pushed all the way, so scale the speed accordingly.
+    let speed = player_params.speed * speed_boost.map_or(1.0, |boost| boost.factor);
+    walk.desired_velocity = desired_velocity.clamp_length_max(1.0) * speed;
+
     // Feed the basis
-    controller.basis(TnuaBuiltinWalk {
-        desired_velocity: desired_velocity.normalize_or_zero() * player_params.speed,
-        desired_forward: desired_forward.normalize_or_zero(),
-        float_height: player_params.float_height,
-        cling_distance: player_params.cling_distance,
-        ..default()
-    });
+    controller.basis(walk);
 
     // Dash
-    if keyboard.pressed(KeyCode::ShiftLeft) {
+    if player_params.can_crouch && actions.pressed(Action::Crouch) {
         controller.action(TnuaBuiltinCrouch {
             float_offset: player_params.crouch_float_offset,
             ..default()
@@ -300,21 +553,66 @@ fn apply_controls(
 
     // Feed the jump action every frame as long as the player holds the jump button. If the player
     // stops holding the jump button, simply stop feeding the action.
-    if keyboard.pressed(KeyCode::Space) {
+    let jump_height = player_params
+        .jump_height
+        .filter(|_| actions.pressed(Action::Jump));
+    if let Some(height) = jump_height {
         controller.action(TnuaBuiltinJump {
             // The height is the only mandatory field of the jump button.
-            height: 2.0,
+            height,
             // `TnuaBuiltinJump` also has customization fields with sensible defaults.
             ..Default::default()
         });
     }
 }
 
-fn move_camera(mut rig: Query<&mut Rig>, tracked: Query<&Transform, With<CameraTracked>>) {
-    let mut rig = rig.single_mut();
-    let tracked = tracked.single();
+fn move_camera(
+    mut rigs: Query<(&mut Rig, &CameraRig)>,
+    orbit: Query<&CameraOrbit, With<MainCamera>>,
+    tracked: Query<(Entity, &Transform, Option<&PlayerParams>), With<CameraTracked>>,
+    spatial_query: SpatialQuery,
+) {
+    let orbit = orbit.single();
+    let (entity, tracked, params) = tracked.single();
+
+    let rotation = match params.map(|p| p.movement_mode) {
+        // Movement depends on the camera's rotation, so following the character's rotation too
+        // would feed back into the input.
+        Some(MovementMode::CameraRelative) => orbit.rotation(),
+        _ => tracked.rotation * orbit.rotation(),
+    };
+
+    // Shorten the arm if there's something between the camera and the character, so that the
+    // character is never hidden behind a wall.
+    let arm_offset = orbit.arm_offset();
+    let arm_length = Dir3::new(rotation * arm_offset)
+        .ok()
+        .and_then(|direction| {
+            spatial_query.cast_shape(
+                &Collider::sphere(CameraOrbit::COLLISION_RADIUS),
+                tracked.translation,
+                Quat::IDENTITY,
+                direction,
+                orbit.distance,
+                true,
+                SpatialQueryFilter::from_mask(GameLayer::Default).with_excluded_entities([entity]),
+            )
+        })
+        .map_or(orbit.distance, |hit| hit.time_of_impact);
 
-    rig.driver_mut::<Position>().position = tracked.translation;
-    rig.driver_mut::<Rotation>().rotation = tracked.rotation;
-    rig.driver_mut::<LookAt>().target = tracked.translation;
+    for (mut rig, camera_rig) in &mut rigs {
+        match camera_rig.mode {
+            CameraMode::Chase => {
+                rig.driver_mut::<Position>().position = tracked.translation;
+                rig.driver_mut::<Rotation>().rotation = rotation;
+                rig.driver_mut::<Arm>().offset = arm_offset.normalize_or_zero() * arm_length;
+                rig.driver_mut::<LookAt>().target = tracked.translation;
+            }
+            CameraMode::FirstPerson => {
+                rig.driver_mut::<Position>().position = tracked.translation + Vec3::Y * EYE_HEIGHT;
+                rig.driver_mut::<Rotation>().rotation = rotation;
+            }
+            CameraMode::Fixed | CameraMode::FreeFly => {}
+        }
+    }
 }
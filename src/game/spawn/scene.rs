@@ -1,10 +1,103 @@
-use bevy::prelude::*;
+//! Spawn glTF scenes as static level geometry.
+
+use avian3d::prelude::{ColliderConstructorHierarchy, RigidBody};
+use bevy::{prelude::*, scene::SceneInstanceReady};
+
+use crate::{
+    game::{level::SceneCollider, physics::Surface},
+    screen::Screen,
+    AppSet,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.observe(spawn_scene);
+    app.add_systems(
+        Update,
+        attach_scene_colliders
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
 }
 
+/// Trigger this event to spawn a glTF scene with static colliders.
 #[derive(Event, Debug)]
-pub struct SpawnScene;
+pub struct SpawnScene {
+    pub name: String,
+    pub scene: Handle<Scene>,
+    pub transform: Transform,
+    /// The collider to use for meshes that don't specify one.
+    pub collider: SceneCollider,
+    /// The surface of all the colliders of the scene.
+    pub surface: Surface,
+}
+
+/// Default collider for a scene whose colliders haven't been created yet.
+#[derive(Component, Debug)]
+struct PendingSceneColliders(SceneCollider);
+
+fn spawn_scene(trigger: Trigger<SpawnScene>, mut commands: Commands) {
+    let event = trigger.event();
+    commands.spawn((
+        Name::new(event.name.clone()),
+        SceneBundle {
+            scene: event.scene.clone(),
+            transform: event.transform,
+            ..default()
+        },
+        RigidBody::Static,
+        PendingSceneColliders(event.collider),
+        event.surface,
+        StateScoped(Screen::Playing),
+    ));
+}
+
+/// Once a scene has been instantiated, look at the names and extras of its nodes to figure out
+/// which collider each mesh should have, and let avian build them.
+fn attach_scene_colliders(
+    mut commands: Commands,
+    mut ready_events: EventReader<SceneInstanceReady>,
+    pending: Query<&PendingSceneColliders>,
+    children: Query<&Children>,
+    parents: Query<&Parent>,
+    meshes: Query<(), With<Handle<Mesh>>>,
+    nodes: Query<(Option<&Name>, Option<&GltfExtras>)>,
+) {
+    for event in ready_events.read() {
+        let Ok(PendingSceneColliders(default_collider)) = pending.get(event.parent) else {
+            continue;
+        };
+
+        let mut hierarchy = ColliderConstructorHierarchy::new(default_collider.constructor());
+        for entity in children.iter_descendants(event.parent) {
+            if !meshes.contains(entity) {
+                continue;
+            }
+            let Ok((Some(mesh_name), _)) = nodes.get(entity) else {
+                continue;
+            };
+            // Mesh primitives are spawned as children of their node, so look at both.
+            let collider = [Some(entity), parents.get(entity).ok().map(Parent::get)]
+                .into_iter()
+                .flatten()
+                .filter_map(|node| nodes.get(node).ok())
+                .find_map(|(name, extras)| {
+                    extras
+                        .and_then(|extras| SceneCollider::from_extras(&extras.value))
+                        .or_else(|| name.and_then(|name| SceneCollider::from_name_suffix(name)))
+                });
 
-fn spawn_scene(_trigger: Trigger<SpawnScene>) {}
+            hierarchy = match collider.map(SceneCollider::constructor) {
+                Some(Some(constructor)) => {
+                    hierarchy.with_constructor_for_name(mesh_name, constructor)
+                }
+                Some(None) => hierarchy.without_constructor_for_name(mesh_name),
+                None => hierarchy,
+            };
+        }
+
+        commands
+            .entity(event.parent)
+            .remove::<PendingSceneColliders>()
+            .insert(hierarchy);
+    }
+}
@@ -0,0 +1,150 @@
+//! Graphics settings, chosen by the player in the options and saved to the `graphics` file.
+//!
+//! Depth of field is applied along with the other camera parameters, see [`crate::camera::physical`].
+
+use bevy::{
+    core_pipeline::bloom::BloomSettings,
+    pbr::DirectionalLightShadowMap,
+    prelude::*,
+    window::{PresentMode, PrimaryWindow, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{camera::MainCamera, storage};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(GraphicsSettings::load())
+        .register_type::<GraphicsSettings>()
+        .add_systems(
+            Update,
+            (apply_graphics_settings, save_graphics_settings)
+                .run_if(resource_changed::<GraphicsSettings>),
+        );
+}
+
+/// Shadow map sizes to pick from, in texels.
+pub const SHADOW_MAP_SIZES: [usize; 4] = [512, 1024, 2048, 4096];
+/// Samples per pixel to pick from for multisample anti-aliasing.
+#[cfg(not(target_family = "wasm"))]
+pub const MSAA_SAMPLES: &[u32] = &[1, 2, 4, 8];
+/// Samples per pixel to pick from for multisample anti-aliasing. WebGL2 only supports 4.
+#[cfg(target_family = "wasm")]
+pub const MSAA_SAMPLES: &[u32] = &[1, 4];
+#[cfg(not(target_family = "wasm"))]
+pub const WINDOW_MODES: [WindowMode; 3] = [
+    WindowMode::Windowed,
+    WindowMode::BorderlessFullscreen,
+    WindowMode::Fullscreen,
+];
+
+#[derive(Resource, Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    /// Size of the directional light shadow maps, in texels.
+    pub shadow_map_size: usize,
+    pub bloom: bool,
+    pub depth_of_field: bool,
+    /// Samples per pixel for multisample anti-aliasing, 1 to turn it off.
+    pub msaa_samples: u32,
+    pub vsync: bool,
+    pub window_mode: WindowMode,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            shadow_map_size: 2048,
+            bloom: true,
+            depth_of_field: true,
+            msaa_samples: 4,
+            vsync: true,
+            window_mode: WindowMode::Windowed,
+        }
+    }
+}
+
+impl GraphicsSettings {
+    const FILE_NAME: &'static str = "graphics";
+
+    fn load() -> Self {
+        storage::load(Self::FILE_NAME).unwrap_or_default()
+    }
+
+    fn msaa(&self) -> Msaa {
+        match self.msaa_samples {
+            0 | 1 => Msaa::Off,
+            #[cfg(target_family = "wasm")]
+            _ => Msaa::Sample4,
+            #[cfg(not(target_family = "wasm"))]
+            2 => Msaa::Sample2,
+            #[cfg(not(target_family = "wasm"))]
+            3 | 4 => Msaa::Sample4,
+            #[cfg(not(target_family = "wasm"))]
+            _ => Msaa::Sample8,
+        }
+    }
+}
+
+/// The value `offset` places after `value` in `values`, wrapping around.
+pub fn cycle<T: Copy + PartialEq>(values: &[T], value: T, offset: isize) -> T {
+    let index = values.iter().position(|v| *v == value).unwrap_or_default();
+    values[(index as isize + offset).rem_euclid(values.len() as isize) as usize]
+}
+
+fn apply_graphics_settings(
+    mut commands: Commands,
+    settings: Res<GraphicsSettings>,
+    mut shadow_map: ResMut<DirectionalLightShadowMap>,
+    mut msaa: ResMut<Msaa>,
+    camera: Query<Entity, With<MainCamera>>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    shadow_map.size = settings.shadow_map_size;
+    *msaa = settings.msaa();
+
+    for camera in &camera {
+        let mut camera = commands.entity(camera);
+        if settings.bloom {
+            camera.insert(BloomSettings::NATURAL);
+        } else {
+            camera.remove::<BloomSettings>();
+        }
+    }
+
+    if let Ok(mut window) = window.get_single_mut() {
+        window.present_mode = if settings.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+        window.mode = settings.window_mode;
+    }
+}
+
+fn save_graphics_settings(settings: Res<GraphicsSettings>) {
+    // The resource is inserted at startup, there's nothing new to save.
+    if settings.is_added() {
+        return;
+    }
+    storage::save(GraphicsSettings::FILE_NAME, &*settings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycle_wraps_around() {
+        let values = [1, 2, 4, 8];
+        assert_eq!(cycle(&values, 2, 1), 4);
+        assert_eq!(cycle(&values, 8, 1), 1);
+        assert_eq!(cycle(&values, 1, -1), 8);
+        assert_eq!(cycle(&values, 4, 6), 1);
+    }
+
+    #[test]
+    fn cycle_starts_from_the_first_value_when_missing() {
+        assert_eq!(cycle(&[1, 2, 4, 8], 3, 1), 2);
+    }
+}
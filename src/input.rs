@@ -0,0 +1,456 @@
+//! Rebindable input actions.
+//!
+//! Gameplay systems shouldn't read the keyboard directly. Instead, [`InputBindings`] maps physical
+//! inputs to [`Action`]s, which are recorded into [`ActionState`] during [`AppSet::RecordInput`].
+//!
+//! Key bindings use [`KeyCode`]s, which are physical key positions: the default `WASD` bindings
+//! end up on `ZQSD` on an AZERTY keyboard, for instance.
+
+use std::collections::BTreeMap;
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{storage, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(InputBindings::load())
+        .init_resource::<ActionState>()
+        .register_type::<Action>()
+        .register_type::<Binding>()
+        .add_systems(
+            Update,
+            (
+                record_actions.in_set(AppSet::RecordInput),
+                save_bindings.run_if(resource_changed::<InputBindings>),
+            ),
+        );
+}
+
+/// Something the player can do.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect, Serialize, Deserialize,
+)]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    TurnLeft,
+    TurnRight,
+    Jump,
+    Crouch,
+    /// Orbit the camera around the player.
+    LookLeft,
+    LookRight,
+    LookUp,
+    LookDown,
+    /// Switch between the first-person and chase cameras.
+    ToggleView,
+    /// Open or close the pause menu.
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 12] = [
+        Action::MoveForward,
+        Action::MoveBackward,
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::Jump,
+        Action::Crouch,
+        Action::LookLeft,
+        Action::LookRight,
+        Action::LookUp,
+        Action::LookDown,
+        Action::ToggleView,
+        Action::Pause,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveForward => "Move forward",
+            Action::MoveBackward => "Move backward",
+            Action::TurnLeft => "Turn left",
+            Action::TurnRight => "Turn right",
+            Action::Jump => "Jump",
+            Action::Crouch => "Crouch",
+            Action::LookLeft => "Look left",
+            Action::LookRight => "Look right",
+            Action::LookUp => "Look up",
+            Action::LookDown => "Look down",
+            Action::ToggleView => "Toggle view",
+            Action::Pause => "Pause",
+        }
+    }
+}
+
+/// A physical input that can trigger an [`Action`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    GamepadButton(GamepadButtonType),
+    /// One direction of an analog axis, e.g. the left stick pushed up.
+    GamepadAxis(GamepadAxisType, AxisDirection),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+impl AxisDirection {
+    /// How much an axis at `value` is pushed in this direction, between 0 and 1.
+    fn value(self, value: f32) -> f32 {
+        match self {
+            AxisDirection::Positive => value.max(0.0),
+            AxisDirection::Negative => (-value).max(0.0),
+        }
+    }
+}
+
+impl Binding {
+    /// A short description of the input, for the UI.
+    pub fn name(self) -> String {
+        match self {
+            Binding::Key(key) => format!("{key:?}"),
+            Binding::Mouse(button) => format!("Mouse {button:?}"),
+            Binding::GamepadButton(button) => format!("{button:?}"),
+            Binding::GamepadAxis(axis, AxisDirection::Positive) => format!("{axis:?}+"),
+            Binding::GamepadAxis(axis, AxisDirection::Negative) => format!("{axis:?}-"),
+        }
+    }
+}
+
+/// Returned when trying to bind an input that is already used by another action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("{binding:?} is already bound to {action:?}")]
+pub struct BindingConflict {
+    pub binding: Binding,
+    pub action: Action,
+}
+
+/// Which inputs trigger which actions. Saved to the `bindings` file whenever it changes.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputBindings(BTreeMap<Action, Vec<Binding>>);
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use AxisDirection::*;
+        use Binding::*;
+        use GamepadAxisType::*;
+
+        Self(BTreeMap::from([
+            (
+                Action::MoveForward,
+                vec![
+                    Key(KeyCode::ArrowUp),
+                    Key(KeyCode::KeyW),
+                    GamepadAxis(LeftStickY, Positive),
+                ],
+            ),
+            (
+                Action::MoveBackward,
+                vec![
+                    Key(KeyCode::ArrowDown),
+                    Key(KeyCode::KeyS),
+                    GamepadAxis(LeftStickY, Negative),
+                ],
+            ),
+            (
+                Action::TurnLeft,
+                vec![
+                    Key(KeyCode::ArrowLeft),
+                    Key(KeyCode::KeyA),
+                    GamepadAxis(LeftStickX, Negative),
+                ],
+            ),
+            (
+                Action::TurnRight,
+                vec![
+                    Key(KeyCode::ArrowRight),
+                    Key(KeyCode::KeyD),
+                    GamepadAxis(LeftStickX, Positive),
+                ],
+            ),
+            (
+                Action::Jump,
+                vec![Key(KeyCode::Space), GamepadButton(GamepadButtonType::South)],
+            ),
+            (
+                Action::Crouch,
+                vec![
+                    Key(KeyCode::ShiftLeft),
+                    GamepadButton(GamepadButtonType::East),
+                ],
+            ),
+            (Action::LookLeft, vec![GamepadAxis(RightStickX, Negative)]),
+            (Action::LookRight, vec![GamepadAxis(RightStickX, Positive)]),
+            (Action::LookUp, vec![GamepadAxis(RightStickY, Positive)]),
+            (Action::LookDown, vec![GamepadAxis(RightStickY, Negative)]),
+            (
+                Action::ToggleView,
+                vec![Key(KeyCode::KeyV), GamepadButton(GamepadButtonType::North)],
+            ),
+            (
+                Action::Pause,
+                vec![
+                    Key(KeyCode::Escape),
+                    GamepadButton(GamepadButtonType::Start),
+                ],
+            ),
+        ]))
+    }
+}
+
+impl InputBindings {
+    const FILE_NAME: &'static str = "bindings";
+
+    /// Load the user's bindings, falling back to the defaults for actions that aren't bound.
+    fn load() -> Self {
+        let mut bindings = Self::default();
+        let Some(user_bindings) = storage::load::<Self>(Self::FILE_NAME) else {
+            return bindings;
+        };
+        bindings.0.extend(user_bindings.0);
+
+        // The file may have been edited by hand: only keep the first action using a given input.
+        for conflict in bindings.conflicts() {
+            warn!("Ignoring conflicting binding: {conflict}");
+            for action in Action::ALL.into_iter().filter(|a| *a > conflict.action) {
+                bindings.unbind(action, conflict.binding);
+            }
+        }
+        bindings
+    }
+
+    /// The inputs bound to `action`.
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The action `binding` is bound to, if any.
+    pub fn action_for(&self, binding: Binding) -> Option<Action> {
+        self.0
+            .iter()
+            .find(|(_, bindings)| bindings.contains(&binding))
+            .map(|(action, _)| *action)
+    }
+
+    /// Bind `binding` to `action` in place of its input at `index`, or in addition to its inputs
+    /// if `index` is past the last one, unless it is already used by another action.
+    pub fn replace(
+        &mut self,
+        action: Action,
+        index: usize,
+        binding: Binding,
+    ) -> Result<(), BindingConflict> {
+        match self.action_for(binding) {
+            Some(existing) if existing == action => Ok(()),
+            Some(existing) => Err(BindingConflict {
+                binding,
+                action: existing,
+            }),
+            None => {
+                let bindings = self.0.entry(action).or_default();
+                match bindings.get_mut(index) {
+                    Some(slot) => *slot = binding,
+                    None => bindings.push(binding),
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn unbind(&mut self, action: Action, binding: Binding) {
+        if let Some(bindings) = self.0.get_mut(&action) {
+            bindings.retain(|b| *b != binding);
+        }
+    }
+
+    /// Every input bound to more than one action, along with the first action using it.
+    pub fn conflicts(&self) -> Vec<BindingConflict> {
+        let mut seen = HashMap::new();
+        let mut conflicts = Vec::new();
+        for (action, bindings) in &self.0 {
+            for binding in bindings {
+                match seen.get(binding) {
+                    Some(first) if first != action => conflicts.push(BindingConflict {
+                        binding: *binding,
+                        action: *first,
+                    }),
+                    Some(_) => {}
+                    None => {
+                        seen.insert(*binding, *action);
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+/// The state of every [`Action`] for the current frame.
+#[derive(Resource, Debug, Default)]
+pub struct ActionState {
+    current: HashMap<Action, f32>,
+    previous: HashMap<Action, f32>,
+}
+
+impl ActionState {
+    /// How much `action` is activated, between 0 and 1.
+    pub fn value(&self, action: Action) -> f32 {
+        self.current.get(&action).copied().unwrap_or_default()
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.value(action) > 0.5
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.pressed(action) && self.previous.get(&action).copied().unwrap_or_default() <= 0.5
+    }
+
+    /// Combine two opposite actions into a value between -1 and 1.
+    pub fn axis(&self, negative: Action, positive: Action) -> f32 {
+        self.value(positive) - self.value(negative)
+    }
+
+    fn record(&mut self, action: Action, value: f32) {
+        let current = self.current.entry(action).or_default();
+        *current = current.max(value);
+    }
+}
+
+fn record_actions(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    bindings: Res<InputBindings>,
+    mut state: ResMut<ActionState>,
+) {
+    let state = &mut *state;
+    std::mem::swap(&mut state.previous, &mut state.current);
+    state.current.clear();
+
+    for (action, bindings) in &bindings.0 {
+        for binding in bindings {
+            let value = match *binding {
+                Binding::Key(key) => button_value(keyboard.pressed(key)),
+                Binding::Mouse(button) => button_value(mouse.pressed(button)),
+                Binding::GamepadButton(button_type) => {
+                    button_value(gamepads.iter().any(|gamepad| {
+                        gamepad_buttons.pressed(GamepadButton::new(gamepad, button_type))
+                    }))
+                }
+                Binding::GamepadAxis(axis_type, direction) => gamepads
+                    .iter()
+                    .filter_map(|gamepad| gamepad_axes.get(GamepadAxis::new(gamepad, axis_type)))
+                    .map(|value| direction.value(value))
+                    .fold(0.0, f32::max),
+            };
+            state.record(*action, value);
+        }
+    }
+}
+
+fn button_value(pressed: bool) -> f32 {
+    if pressed {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+fn save_bindings(bindings: Res<InputBindings>) {
+    // The resource is inserted at startup, there's nothing new to save.
+    if bindings.is_added() {
+        return;
+    }
+    storage::save(InputBindings::FILE_NAME, &*bindings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPACE: Binding = Binding::Key(KeyCode::Space);
+
+    #[test]
+    fn default_bindings_have_no_conflicts() {
+        assert_eq!(InputBindings::default().conflicts(), Vec::new());
+    }
+
+    #[test]
+    fn replace_rejects_inputs_of_other_actions() {
+        let mut bindings = InputBindings::default();
+        assert_eq!(bindings.action_for(SPACE), Some(Action::Jump));
+        assert_eq!(
+            bindings.replace(Action::Crouch, 0, SPACE),
+            Err(BindingConflict {
+                binding: SPACE,
+                action: Action::Jump,
+            })
+        );
+        assert!(!bindings.bindings(Action::Crouch).contains(&SPACE));
+    }
+
+    #[test]
+    fn replace_only_changes_one_slot() {
+        let mut bindings = InputBindings::default();
+        let before = bindings.bindings(Action::MoveForward).to_vec();
+        // Free the input first.
+        bindings.unbind(Action::Jump, SPACE);
+        assert_eq!(bindings.replace(Action::MoveForward, 1, SPACE), Ok(()));
+        let after = bindings.bindings(Action::MoveForward);
+        assert_eq!(after.len(), before.len());
+        assert_eq!(after[0], before[0]);
+        assert_eq!(after[1], SPACE);
+        assert_eq!(after[2..], before[2..]);
+    }
+
+    #[test]
+    fn replace_past_the_end_adds_an_input() {
+        let mut bindings = InputBindings::default();
+        let count = bindings.bindings(Action::Jump).len();
+        let binding = Binding::Key(KeyCode::KeyJ);
+        assert_eq!(bindings.replace(Action::Jump, count, binding), Ok(()));
+        assert_eq!(bindings.bindings(Action::Jump).len(), count + 1);
+        assert_eq!(bindings.action_for(binding), Some(Action::Jump));
+    }
+
+    #[test]
+    fn replace_is_idempotent() {
+        let mut bindings = InputBindings::default();
+        let before = bindings.clone();
+        assert_eq!(bindings.replace(Action::Jump, 0, SPACE), Ok(()));
+        assert_eq!(bindings, before);
+    }
+
+    #[test]
+    fn unbind_frees_the_input() {
+        let mut bindings = InputBindings::default();
+        bindings.unbind(Action::Jump, SPACE);
+        assert_eq!(bindings.action_for(SPACE), None);
+        assert_eq!(bindings.replace(Action::Crouch, 0, SPACE), Ok(()));
+        assert_eq!(bindings.action_for(SPACE), Some(Action::Crouch));
+    }
+
+    #[test]
+    fn conflicts_name_the_first_action() {
+        let bindings = InputBindings(BTreeMap::from([
+            (Action::Jump, vec![SPACE]),
+            (Action::Crouch, vec![SPACE]),
+        ]));
+        assert_eq!(
+            bindings.conflicts(),
+            vec![BindingConflict {
+                binding: SPACE,
+                action: Action::Jump,
+            }]
+        );
+    }
+}
@@ -2,13 +2,16 @@ mod camera;
 #[cfg(feature = "dev")]
 mod dev_tools;
 mod game;
+mod graphics;
+mod input;
 mod screen;
+mod storage;
 mod ui;
 
 use avian3d::PhysicsPlugins;
 use bevy::{
     asset::AssetMetaCheck,
-    audio::{AudioPlugin, Volume},
+    audio::{AudioPlugin, SpatialScale, Volume},
     prelude::*,
 };
 use bevy_tnua::prelude::TnuaControllerPlugin;
@@ -49,7 +52,8 @@ impl Plugin for AppPlugin {
                     global_volume: GlobalVolume {
                         volume: Volume::new(0.3),
                     },
-                    ..default()
+                    // Spatial sounds fade with the square of the distance beyond 6 meters.
+                    default_spatial_scale: SpatialScale::new(1.0 / 6.0),
                 }),
             PhysicsPlugins::default(),
             TnuaControllerPlugin::default(),
@@ -57,7 +61,14 @@ impl Plugin for AppPlugin {
         ));
 
         // Add other plugins.
-        app.add_plugins((camera::plugin, game::plugin, screen::plugin, ui::plugin));
+        app.add_plugins((
+            camera::plugin,
+            game::plugin,
+            graphics::plugin,
+            input::plugin,
+            screen::plugin,
+            ui::plugin,
+        ));
 
         // Enable dev tools for dev builds.
         #[cfg(feature = "dev")]
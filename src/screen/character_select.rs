@@ -0,0 +1,393 @@
+//! The character selection screen, between the title screen and the game.
+//!
+//! The selected character is rendered to a texture by a dedicated camera, so that the preview
+//! doesn't depend on where the main camera is.
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        view::RenderLayers,
+    },
+    ui::Val::*,
+};
+
+use super::Screen;
+use crate::{
+    game::{
+        accessory::Equipment,
+        animation::{AnimationStateMachine, AnimationStates},
+        assets::CharactersAssets,
+        character::{Character, SelectedAccessories, SelectedCharacter},
+        locomotion::{LocomotionProfile, LocomotionProfiles, SelectedLocomotion},
+        spawn::player::PlayerAssets,
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::CharacterSelect), enter_character_select);
+
+    app.register_type::<CharacterSelectAction>();
+    app.add_systems(
+        Update,
+        (
+            handle_character_select_action,
+            update_preview,
+            update_preview_accessories,
+            update_character_name,
+            update_locomotion_name,
+            rotate_preview,
+        )
+            .chain()
+            .run_if(in_state(Screen::CharacterSelect)),
+    );
+    // Scenes are spawned right before `PostUpdate`, so the preview never shows up in the main
+    // camera.
+    app.add_systems(
+        PostUpdate,
+        move_preview_to_layer.run_if(in_state(Screen::CharacterSelect)),
+    );
+}
+
+/// Render layer of the preview, so that only the preview camera sees it.
+const PREVIEW_LAYER: usize = 1;
+/// Size of the preview texture, in pixels.
+const PREVIEW_SIZE: u32 = 384;
+/// Rotation speed of the preview, in radians per second.
+const PREVIEW_TURN_SPEED: f32 = 1.0;
+/// The accessories that can be worn, with their label and name in the accessory catalog.
+const ACCESSORIES: [(&str, &str); 4] = [
+    ("Glasses", "glasses"),
+    ("Sunglasses", "sunglasses"),
+    ("Mask", "mask"),
+    ("Hearing aid", "hearing-aid"),
+];
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum CharacterSelectAction {
+    Previous,
+    Next,
+    CycleLocomotion,
+    ToggleAccessory(&'static str),
+    Play,
+    Back,
+}
+
+/// The rotating platform the previewed character stands on.
+#[derive(Component, Debug, Default)]
+struct PreviewTurntable {
+    character: Option<Character>,
+    locomotion: Option<LocomotionProfile>,
+}
+
+#[derive(Component, Debug)]
+struct CharacterNameLabel;
+
+#[derive(Component, Debug)]
+struct LocomotionNameLabel;
+
+fn enter_character_select(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let preview = images.add(preview_image());
+
+    commands.spawn((
+        Name::new("Preview Camera"),
+        Camera3dBundle {
+            camera: Camera {
+                target: RenderTarget::Image(preview.clone()),
+                // Render before the main camera, which displays the preview.
+                order: -1,
+                clear_color: ClearColorConfig::Custom(Color::NONE),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.9, 2.5).looking_at(Vec3::Y * 0.5, Vec3::Y),
+            ..default()
+        },
+        RenderLayers::layer(PREVIEW_LAYER),
+        StateScoped(Screen::CharacterSelect),
+    ));
+    commands.spawn((
+        Name::new("Preview Light"),
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                illuminance: 2000.0,
+                ..default()
+            },
+            transform: Transform::from_xyz(2.0, 4.0, 3.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+        RenderLayers::layer(PREVIEW_LAYER),
+        StateScoped(Screen::CharacterSelect),
+    ));
+    commands.spawn((
+        Name::new("Preview Turntable"),
+        PreviewTurntable::default(),
+        SpatialBundle::default(),
+        StateScoped(Screen::CharacterSelect),
+    ));
+
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::CharacterSelect))
+        .with_children(|children| {
+            children.header("Choose your character");
+            children
+                .spawn((
+                    Name::new("Preview Row"),
+                    NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Px(10.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    children.button("<").insert(CharacterSelectAction::Previous);
+                    children.spawn((
+                        Name::new("Preview"),
+                        ImageBundle {
+                            style: Style {
+                                width: Px(PREVIEW_SIZE as f32),
+                                height: Px(PREVIEW_SIZE as f32),
+                                ..default()
+                            },
+                            image: UiImage::new(preview),
+                            ..default()
+                        },
+                    ));
+                    children.button(">").insert(CharacterSelectAction::Next);
+                });
+            children.label("").insert(CharacterNameLabel);
+            children
+                .spawn((
+                    Name::new("Locomotion Row"),
+                    NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Px(10.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    children
+                        .button("Mobility")
+                        .insert(CharacterSelectAction::CycleLocomotion);
+                    children.label("").insert(LocomotionNameLabel);
+                });
+            children
+                .spawn((
+                    Name::new("Accessories Row"),
+                    NodeBundle {
+                        style: Style {
+                            column_gap: Px(10.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    for (label, accessory) in ACCESSORIES {
+                        children
+                            .button(label)
+                            .insert(CharacterSelectAction::ToggleAccessory(accessory));
+                    }
+                });
+            children.button("Play").insert(CharacterSelectAction::Play);
+            children.button("Back").insert(CharacterSelectAction::Back);
+        });
+}
+
+fn preview_image() -> Image {
+    let size = Extent3d {
+        width: PREVIEW_SIZE,
+        height: PREVIEW_SIZE,
+        ..default()
+    };
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+fn handle_character_select_action(
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut selected: ResMut<SelectedCharacter>,
+    mut selected_locomotion: ResMut<SelectedLocomotion>,
+    mut selected_accessories: ResMut<SelectedAccessories>,
+    mut button_query: InteractionQuery<&CharacterSelectAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                CharacterSelectAction::Previous => selected.0 = selected.0.cycle(-1),
+                CharacterSelectAction::Next => selected.0 = selected.0.cycle(1),
+                CharacterSelectAction::CycleLocomotion => {
+                    selected_locomotion.0 = selected_locomotion.0.cycle(1)
+                }
+                CharacterSelectAction::ToggleAccessory(accessory) => {
+                    selected_accessories.toggle(accessory)
+                }
+                CharacterSelectAction::Play => {
+                    commands.add(PlayerAssets::rebuild);
+                    next_screen.set(Screen::Playing);
+                }
+                CharacterSelectAction::Back => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
+
+/// Show the selected character on the turntable, as it gets around.
+fn update_preview(
+    mut commands: Commands,
+    selected: Res<SelectedCharacter>,
+    selected_locomotion: Res<SelectedLocomotion>,
+    characters_assets: Res<CharactersAssets>,
+    selected_accessories: Res<SelectedAccessories>,
+    gltfs: Res<Assets<Gltf>>,
+    locomotion_profiles: Res<Assets<LocomotionProfiles>>,
+    animation_states: Res<Assets<AnimationStates>>,
+    mut graphs: ResMut<Assets<AnimationGraph>>,
+    mut turntable: Query<(Entity, &mut PreviewTurntable)>,
+) {
+    let Ok((entity, mut turntable)) = turntable.get_single_mut() else {
+        return;
+    };
+    if turntable.character == Some(selected.0)
+        && turntable.locomotion == Some(selected_locomotion.0)
+    {
+        return;
+    }
+    let Some(locomotion) = locomotion_profiles
+        .get(&characters_assets.locomotion)
+        .map(|profiles| &profiles[selected_locomotion.0])
+    else {
+        return;
+    };
+    let (Some(gltf), Some(animation_states)) = (
+        gltfs.get(&characters_assets[selected.0]),
+        animation_states.get(&locomotion.animation_states),
+    ) else {
+        return;
+    };
+    turntable.character = Some(selected.0);
+    turntable.locomotion = Some(selected_locomotion.0);
+    let mut animations = AnimationStateMachine::new(animation_states, gltf, &mut graphs);
+    animations.set_state("standing", 1.0);
+
+    commands
+        .entity(entity)
+        .despawn_descendants()
+        .with_children(|children| {
+            children.spawn((
+                Name::new(selected.0.name()),
+                SceneBundle {
+                    scene: gltf.scenes[0].clone(),
+                    ..default()
+                },
+                animations,
+                Equipment::new(
+                    locomotion
+                        .accessory
+                        .iter()
+                        .chain(&selected_accessories.0)
+                        .cloned(),
+                ),
+            ));
+        });
+}
+
+fn update_preview_accessories(
+    selected_accessories: Res<SelectedAccessories>,
+    selected_locomotion: Res<SelectedLocomotion>,
+    characters_assets: Res<CharactersAssets>,
+    locomotion_profiles: Res<Assets<LocomotionProfiles>>,
+    mut equipment: Query<&mut Equipment>,
+) {
+    if !selected_accessories.is_changed() {
+        return;
+    }
+    let Some(locomotion) = locomotion_profiles
+        .get(&characters_assets.locomotion)
+        .map(|profiles| &profiles[selected_locomotion.0])
+    else {
+        return;
+    };
+    for mut equipment in &mut equipment {
+        equipment.set(
+            locomotion
+                .accessory
+                .iter()
+                .chain(&selected_accessories.0)
+                .cloned(),
+        );
+    }
+}
+
+fn update_character_name(
+    selected: Res<SelectedCharacter>,
+    labels: Query<&Children, With<CharacterNameLabel>>,
+    mut texts: Query<&mut Text>,
+) {
+    for children in &labels {
+        set_label_text(children, &mut texts, selected.0.name());
+    }
+}
+
+fn update_locomotion_name(
+    selected: Res<SelectedLocomotion>,
+    labels: Query<&Children, With<LocomotionNameLabel>>,
+    mut texts: Query<&mut Text>,
+) {
+    for children in &labels {
+        set_label_text(children, &mut texts, selected.0.name());
+    }
+}
+
+fn set_label_text(children: &Children, texts: &mut Query<&mut Text>, value: &str) {
+    let mut texts = texts.iter_many_mut(children);
+    while let Some(mut text) = texts.fetch_next() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.to_string();
+        }
+    }
+}
+
+fn rotate_preview(time: Res<Time>, mut turntable: Query<&mut Transform, With<PreviewTurntable>>) {
+    for mut transform in &mut turntable {
+        transform.rotate_y(PREVIEW_TURN_SPEED * time.delta_seconds());
+    }
+}
+
+/// Scenes don't propagate render layers to their meshes, so do it by hand.
+fn move_preview_to_layer(
+    mut commands: Commands,
+    meshes: Query<Entity, Added<Handle<Mesh>>>,
+    parents: Query<&Parent>,
+    turntables: Query<(), With<PreviewTurntable>>,
+) {
+    for mesh in &meshes {
+        if parents
+            .iter_ancestors(mesh)
+            .any(|ancestor| turntables.contains(ancestor))
+        {
+            commands
+                .entity(mesh)
+                .insert(RenderLayers::layer(PREVIEW_LAYER));
+        }
+    }
+}
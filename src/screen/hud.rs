@@ -0,0 +1,84 @@
+//! The HUD shown over the game while playing.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::{
+        goal::{LevelStats, LevelTime},
+        spawn::{
+            level::SpawnLevel,
+            pickup::{PickupKind, SpeedBoost},
+        },
+    },
+    ui::{format, prelude::*},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(spawn_hud)
+        .bind_resource::<LevelStats>()
+        .bind_resource::<LevelTime>()
+        .bind_component::<SpeedBoost>();
+}
+
+/// The HUD is spawned along with each level, and despawned with it.
+fn spawn_hud(_trigger: Trigger<SpawnLevel>, mut commands: Commands) {
+    // What's been collected, for the kinds of pickups the level has.
+    commands
+        .hud(HudAnchor::TopLeft)
+        .insert(StateScoped(Screen::Playing))
+        .with_children(|children| {
+            for kind in PickupKind::COUNTED {
+                children.counter(kind.color(), "").insert((
+                    BindText::new(move |stats: &LevelStats| {
+                        format!("{} / {}", stats.collected(kind), stats.total(kind))
+                    }),
+                    BindDisplay::new(move |stats: &LevelStats| stats.total(kind) > 0),
+                ));
+            }
+        });
+
+    commands
+        .hud(HudAnchor::TopRight)
+        .insert(StateScoped(Screen::Playing))
+        .with_children(|children| {
+            children
+                .hud_label("")
+                .insert(BindText::new(|time: &LevelTime| format::duration(time.0)));
+        });
+
+    commands
+        .hud(HudAnchor::Top)
+        .insert(StateScoped(Screen::Playing))
+        .with_children(|children| {
+            children.hud_label("").insert((
+                BindText::new(|stats: &LevelStats| {
+                    let needed = stats.coins_needed.unwrap_or_default();
+                    let left = needed.saturating_sub(stats.collected(PickupKind::Coin));
+                    match left {
+                        1 => "Collect 1 more coin".to_string(),
+                        _ => format!("Collect {left} more coins"),
+                    }
+                }),
+                BindDisplay::new(|stats: &LevelStats| {
+                    stats
+                        .coins_needed
+                        .is_some_and(|needed| stats.collected(PickupKind::Coin) < needed)
+                }),
+            ));
+        });
+
+    commands
+        .hud(HudAnchor::Bottom)
+        .insert(StateScoped(Screen::Playing))
+        .with_children(|children| {
+            children
+                .hud_label("")
+                .insert(BindText::new(|boost: &SpeedBoost| {
+                    format!("Speed boost {}s", boost.remaining().as_secs() + 1)
+                }));
+            children
+                .progress_bar(PickupKind::SpeedBoost.color())
+                .insert(BindProgress::new(SpeedBoost::fraction_remaining));
+        });
+}
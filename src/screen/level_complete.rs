@@ -0,0 +1,105 @@
+//! The overlay shown when every goal of the level has been met.
+
+use bevy::prelude::*;
+
+use super::{PlayingState, Screen};
+use crate::{
+    game::{
+        assets::{LevelAssets, LevelId},
+        goal::{LevelStats, LevelTime},
+        progress::LevelProgress,
+        spawn::{
+            level::{ChangeLevel, CurrentLevel, RestartLevel},
+            pickup::PickupKind,
+        },
+    },
+    ui::{format, prelude::*},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(PlayingState::Complete), enter_level_complete);
+
+    app.register_type::<LevelCompleteAction>();
+    app.add_systems(
+        Update,
+        handle_level_complete_action.run_if(in_state(PlayingState::Complete)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum LevelCompleteAction {
+    Next(LevelId),
+    Replay,
+    Quit,
+}
+
+fn enter_level_complete(
+    mut commands: Commands,
+    stats: Res<LevelStats>,
+    level_time: Res<LevelTime>,
+    progress: Res<LevelProgress>,
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<LevelAssets>,
+) {
+    let best_time = level_assets
+        .key(current_level.0)
+        .and_then(|key| progress.best_times.get(&key).copied());
+    commands
+        .ui_root()
+        .insert((
+            StateScoped(PlayingState::Complete),
+            BackgroundColor(ui_palette::OVERLAY_BACKGROUND),
+        ))
+        .with_children(|children| {
+            children.header("Level complete");
+            children.label(format!("Time {}", format::duration(level_time.0)));
+            if best_time == Some(level_time.0) {
+                children.label("New best time!");
+            } else if let Some(best_time) = best_time {
+                children.label(format!("Best time {}", format::duration(best_time)));
+            }
+            let coins = stats.total(PickupKind::Coin);
+            if coins > 0 {
+                children.label(format!(
+                    "Collected {} of {coins} coins",
+                    stats.collected(PickupKind::Coin)
+                ));
+            }
+
+            if let Some(next) = level_assets.next(current_level.0) {
+                children
+                    .button("Next level")
+                    .insert(LevelCompleteAction::Next(next));
+            }
+            children
+                .button("Replay")
+                .insert(LevelCompleteAction::Replay);
+            children
+                .button("Quit to title")
+                .insert(LevelCompleteAction::Quit);
+        });
+}
+
+fn handle_level_complete_action(
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut next_state: ResMut<NextState<PlayingState>>,
+    mut button_query: InteractionQuery<&LevelCompleteAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match *action {
+                LevelCompleteAction::Next(id) => {
+                    commands.trigger(ChangeLevel(id));
+                    next_state.set(PlayingState::Running);
+                }
+                LevelCompleteAction::Replay => {
+                    commands.trigger(RestartLevel);
+                    next_state.set(PlayingState::Running);
+                }
+                LevelCompleteAction::Quit => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
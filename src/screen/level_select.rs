@@ -0,0 +1,100 @@
+//! Pick one of the unlocked levels to play, from the title screen.
+
+use bevy::{prelude::*, ui::Val::*};
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::{LevelAssets, LevelId},
+        level::Level,
+        progress::LevelProgress,
+        spawn::level::SelectedLevel,
+    },
+    ui::{format, prelude::*},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::LevelSelect), enter_level_select);
+
+    app.register_type::<LevelSelectAction>();
+    app.add_systems(
+        Update,
+        handle_level_select_action.run_if(in_state(Screen::LevelSelect)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum LevelSelectAction {
+    Play(LevelId),
+    Back,
+}
+
+fn enter_level_select(
+    mut commands: Commands,
+    level_assets: Res<LevelAssets>,
+    levels: Res<Assets<Level>>,
+    progress: Res<LevelProgress>,
+) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::LevelSelect))
+        .with_children(|children| {
+            children.header("Levels");
+            let mut previous = None;
+            for id in level_assets.ids() {
+                let key = level_assets.key(id);
+                let name = level_assets
+                    .get(id)
+                    .and_then(|handle| levels.get(handle))
+                    .map_or_else(|| format!("Level {}", id.0 + 1), |level| level.name.clone());
+                let unlocked = progress.is_unlocked(previous.as_ref());
+                let best_time = key.as_ref().and_then(|key| progress.best_times.get(key));
+                previous = key;
+                let status = match best_time {
+                    Some(time) => format!("Best time {}", format::duration(*time)),
+                    None if unlocked => "Not completed yet".to_string(),
+                    None => "Locked".to_string(),
+                };
+                children
+                    .spawn((
+                        Name::new(format!("{name} Row")),
+                        NodeBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                column_gap: Px(10.0),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    ))
+                    .with_children(|children| {
+                        let mut button = children.button(name);
+                        // Locked levels can't be played.
+                        if unlocked {
+                            button.insert(LevelSelectAction::Play(id));
+                        }
+                        children.label(status);
+                    });
+            }
+            children.button("Back").insert(LevelSelectAction::Back);
+        });
+}
+
+fn handle_level_select_action(
+    mut selected_level: ResMut<SelectedLevel>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut button_query: InteractionQuery<&LevelSelectAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match *action {
+                LevelSelectAction::Play(id) => {
+                    selected_level.0 = id;
+                    next_screen.set(Screen::CharacterSelect);
+                }
+                LevelSelectAction::Back => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
@@ -1,8 +1,15 @@
 //! The game's main screen states and transitions between them.
 
+mod character_select;
 mod credits;
+mod hud;
+mod level_complete;
+mod level_select;
 mod loading;
+mod options;
+mod pause;
 mod playing;
+mod saves;
 mod splash;
 mod title;
 
@@ -11,13 +18,22 @@ use bevy::prelude::*;
 pub(super) fn plugin(app: &mut App) {
     app.init_state::<Screen>();
     app.enable_state_scoped_entities::<Screen>();
+    app.add_sub_state::<PlayingState>();
+    app.enable_state_scoped_entities::<PlayingState>();
 
     app.add_plugins((
         splash::plugin,
         loading::plugin,
         title::plugin,
+        character_select::plugin,
+        options::plugin,
         credits::plugin,
+        level_select::plugin,
+        level_complete::plugin,
         playing::plugin,
+        hud::plugin,
+        pause::plugin,
+        saves::plugin,
     ));
 
     // In dev mode go straight to the loading screen
@@ -32,6 +48,29 @@ pub enum Screen {
     Splash,
     Loading,
     Title,
+    /// Pick one of the unlocked levels.
+    LevelSelect,
+    /// Pick a character before playing.
+    CharacterSelect,
+    /// Change the settings, from the title screen.
+    Options,
+    /// Pick a saved game to load.
+    LoadGame,
     Credits,
     Playing,
 }
+
+/// What's happening while [`Screen::Playing`].
+#[derive(SubStates, Debug, Hash, PartialEq, Eq, Clone, Default)]
+#[source(Screen = Screen::Playing)]
+pub enum PlayingState {
+    #[default]
+    Running,
+    Paused,
+    /// The options, opened from the pause menu.
+    Options,
+    /// Pick a slot to save the game to, from the pause menu.
+    Save,
+    /// Every goal of the level has been met.
+    Complete,
+}
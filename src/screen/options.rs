@@ -0,0 +1,506 @@
+//! The options screen, accessed from the title screen or the pause menu.
+//!
+//! The options are split into tabs. Every change is saved right away by the module that owns the
+//! settings.
+
+#[cfg(not(target_family = "wasm"))]
+use bevy::window::WindowMode;
+use bevy::{core::FrameCount, prelude::*, ui::Val::*};
+
+use super::{PlayingState, Screen};
+use crate::{
+    game::{
+        audio::mixer::{AudioBus, AudioVolumes},
+        spawn::player::MovementMode,
+    },
+    graphics::{self, GraphicsSettings, MSAA_SAMPLES, SHADOW_MAP_SIZES},
+    input::{Action, Binding, InputBindings},
+    ui::prelude::*,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ControlsMenu>();
+    app.add_systems(OnEnter(Screen::Options), enter_options);
+    app.add_systems(OnEnter(PlayingState::Options), enter_pause_options);
+
+    app.register_type::<OptionsAction>();
+    app.add_systems(
+        Update,
+        (
+            // Buttons don't do anything while waiting for an input to bind.
+            handle_options_action.run_if(not(rebinding)),
+            capture_binding.run_if(rebinding),
+            update_option_labels,
+        )
+            .chain()
+            .after(AppSet::Update)
+            .run_if(in_state(Screen::Options).or_else(in_state(PlayingState::Options))),
+    );
+}
+
+/// How much the volume buttons change the volume.
+const VOLUME_STEP: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+enum OptionsTab {
+    Graphics,
+    Audio,
+    Controls,
+}
+
+impl OptionsTab {
+    const ALL: [OptionsTab; 3] = [
+        OptionsTab::Graphics,
+        OptionsTab::Audio,
+        OptionsTab::Controls,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            OptionsTab::Graphics => "Graphics",
+            OptionsTab::Audio => "Audio",
+            OptionsTab::Controls => "Controls",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+enum GraphicsOption {
+    ShadowMapSize,
+    Bloom,
+    DepthOfField,
+    Msaa,
+    // Browsers own the presentation and the window.
+    #[cfg(not(target_family = "wasm"))]
+    Vsync,
+    #[cfg(not(target_family = "wasm"))]
+    WindowMode,
+}
+
+impl GraphicsOption {
+    const ALL: &'static [GraphicsOption] = &[
+        GraphicsOption::ShadowMapSize,
+        GraphicsOption::Bloom,
+        GraphicsOption::DepthOfField,
+        GraphicsOption::Msaa,
+        #[cfg(not(target_family = "wasm"))]
+        GraphicsOption::Vsync,
+        #[cfg(not(target_family = "wasm"))]
+        GraphicsOption::WindowMode,
+    ];
+
+    /// The option and its current value, e.g. "Bloom: On".
+    fn describe(self, settings: &GraphicsSettings) -> String {
+        let on_off = |on| if on { "On" } else { "Off" };
+        match self {
+            GraphicsOption::ShadowMapSize => {
+                format!("Shadow quality: {}", settings.shadow_map_size)
+            }
+            GraphicsOption::Bloom => format!("Bloom: {}", on_off(settings.bloom)),
+            GraphicsOption::DepthOfField => {
+                format!("Depth of field: {}", on_off(settings.depth_of_field))
+            }
+            GraphicsOption::Msaa => match settings.msaa_samples {
+                0 | 1 => "Anti-aliasing: Off".to_string(),
+                samples => format!("Anti-aliasing: {samples}x"),
+            },
+            #[cfg(not(target_family = "wasm"))]
+            GraphicsOption::Vsync => format!("Vsync: {}", on_off(settings.vsync)),
+            #[cfg(not(target_family = "wasm"))]
+            GraphicsOption::WindowMode => {
+                let mode = match settings.window_mode {
+                    WindowMode::Windowed => "Windowed",
+                    WindowMode::BorderlessFullscreen => "Borderless",
+                    WindowMode::SizedFullscreen | WindowMode::Fullscreen => "Fullscreen",
+                };
+                format!("Window: {mode}")
+            }
+        }
+    }
+
+    /// Switch to the next value of the option.
+    fn cycle(self, settings: &mut GraphicsSettings) {
+        match self {
+            GraphicsOption::ShadowMapSize => {
+                settings.shadow_map_size =
+                    graphics::cycle(&SHADOW_MAP_SIZES, settings.shadow_map_size, 1);
+            }
+            GraphicsOption::Bloom => settings.bloom = !settings.bloom,
+            GraphicsOption::DepthOfField => settings.depth_of_field = !settings.depth_of_field,
+            GraphicsOption::Msaa => {
+                settings.msaa_samples = graphics::cycle(MSAA_SAMPLES, settings.msaa_samples, 1);
+            }
+            #[cfg(not(target_family = "wasm"))]
+            GraphicsOption::Vsync => settings.vsync = !settings.vsync,
+            #[cfg(not(target_family = "wasm"))]
+            GraphicsOption::WindowMode => {
+                settings.window_mode =
+                    graphics::cycle(&graphics::WINDOW_MODES, settings.window_mode, 1);
+            }
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+enum OptionsAction {
+    ShowTab(OptionsTab),
+    CycleGraphics(GraphicsOption),
+    /// Change the volume of a bus by the given amount.
+    ChangeVolume(AudioBus, f32),
+    /// Pick the action to rebind, by offset from the current one.
+    CycleAction(isize),
+    /// Pick the input of the action to rebind, by offset from the current one.
+    CycleSlot(isize),
+    /// Wait for an input to bind to the picked slot.
+    Rebind,
+    /// Unbind the input in the picked slot.
+    RemoveBinding,
+    ResetBindings,
+    CycleMovementMode,
+    Back,
+}
+
+/// A label showing the current value of some option.
+#[derive(Component, Debug, Clone, Copy)]
+enum OptionLabel {
+    Graphics(GraphicsOption),
+    Volume(AudioBus),
+    /// The action picked in the controls tab.
+    Action,
+    /// The input of the action picked in the controls tab.
+    Slot,
+    /// The inputs bound to the picked action.
+    Bindings,
+    MovementMode,
+    /// What's going on with the rebinding.
+    ControlsMessage,
+}
+
+/// The part of the screen that changes with the tab.
+#[derive(Component, Debug)]
+struct OptionsContent;
+
+/// State of the controls tab.
+#[derive(Resource, Debug, Default)]
+pub(super) struct ControlsMenu {
+    /// Index of the picked action in [`Action::ALL`].
+    action: usize,
+    /// Index of the picked input in the bindings of the action. One past the last input stands
+    /// for a new input.
+    slot: usize,
+    /// The frame the rebinding started on, if the next input should be bound to the picked slot.
+    rebinding: Option<u32>,
+    message: String,
+}
+
+impl ControlsMenu {
+    fn action(&self) -> Action {
+        Action::ALL[self.action]
+    }
+
+    /// Pick another action, starting from its first input.
+    fn cycle_action(&mut self, offset: isize) {
+        let count = Action::ALL.len() as isize;
+        self.action = (self.action as isize + offset).rem_euclid(count) as usize;
+        self.slot = 0;
+    }
+
+    /// Pick another input of the action, or the new input slot.
+    fn cycle_slot(&mut self, bindings: &InputBindings, offset: isize) {
+        let count = bindings.bindings(self.action()).len() as isize + 1;
+        self.slot = (self.slot as isize + offset).rem_euclid(count) as usize;
+    }
+}
+
+/// Whether the controls tab is waiting for an input to bind. Other systems reading inputs in the
+/// options shouldn't run meanwhile, and must run before [`capture_binding`] so that they don't
+/// see the input that ends the rebinding either.
+pub(super) fn rebinding(controls: Res<ControlsMenu>) -> bool {
+    controls.rebinding.is_some()
+}
+
+fn enter_options(mut commands: Commands, mut controls: ResMut<ControlsMenu>) {
+    *controls = default();
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Options))
+        .with_children(spawn_options);
+}
+
+fn enter_pause_options(mut commands: Commands, mut controls: ResMut<ControlsMenu>) {
+    *controls = default();
+    commands
+        .ui_root()
+        .insert((
+            StateScoped(PlayingState::Options),
+            BackgroundColor(ui_palette::OVERLAY_BACKGROUND),
+        ))
+        .with_children(spawn_options);
+}
+
+fn spawn_options(children: &mut ChildBuilder) {
+    children
+        .spawn((Name::new("Options Tabs"), row()))
+        .with_children(|children| {
+            for tab in OptionsTab::ALL {
+                children
+                    .button(tab.name())
+                    .insert(OptionsAction::ShowTab(tab));
+            }
+        });
+    children
+        .spawn((
+            Name::new("Options Content"),
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Px(10.0),
+                    ..default()
+                },
+                ..default()
+            },
+            OptionsContent,
+        ))
+        .with_children(|children| spawn_tab(children, OptionsTab::Graphics));
+    children.button("Back").insert(OptionsAction::Back);
+}
+
+fn spawn_tab(children: &mut ChildBuilder, tab: OptionsTab) {
+    match tab {
+        OptionsTab::Graphics => {
+            for &option in GraphicsOption::ALL {
+                children
+                    .spawn((Name::new("Graphics Option Row"), row()))
+                    .with_children(|children| {
+                        children.label("").insert(OptionLabel::Graphics(option));
+                        children
+                            .button("Change")
+                            .insert(OptionsAction::CycleGraphics(option));
+                    });
+            }
+        }
+        OptionsTab::Audio => {
+            for bus in AudioBus::ALL {
+                children
+                    .spawn((Name::new(format!("{} Volume Row", bus.name())), row()))
+                    .with_children(|children| {
+                        children
+                            .button("-")
+                            .insert(OptionsAction::ChangeVolume(bus, -VOLUME_STEP));
+                        children.label("").insert(OptionLabel::Volume(bus));
+                        children
+                            .button("+")
+                            .insert(OptionsAction::ChangeVolume(bus, VOLUME_STEP));
+                    });
+            }
+        }
+        OptionsTab::Controls => {
+            children
+                .spawn((Name::new("Action Row"), row()))
+                .with_children(|children| {
+                    children.button("<").insert(OptionsAction::CycleAction(-1));
+                    children.label("").insert(OptionLabel::Action);
+                    children.button(">").insert(OptionsAction::CycleAction(1));
+                });
+            children
+                .spawn((Name::new("Slot Row"), row()))
+                .with_children(|children| {
+                    children.button("<").insert(OptionsAction::CycleSlot(-1));
+                    children.label("").insert(OptionLabel::Slot);
+                    children.button(">").insert(OptionsAction::CycleSlot(1));
+                });
+            children.label("").insert(OptionLabel::Bindings);
+            children
+                .spawn((Name::new("Rebind Row"), row()))
+                .with_children(|children| {
+                    children.button("Rebind").insert(OptionsAction::Rebind);
+                    children
+                        .button("Remove")
+                        .insert(OptionsAction::RemoveBinding);
+                    children
+                        .button("Reset")
+                        .insert(OptionsAction::ResetBindings);
+                });
+            children.label("").insert(OptionLabel::ControlsMessage);
+            children
+                .spawn((Name::new("Movement Mode Row"), row()))
+                .with_children(|children| {
+                    children.label("").insert(OptionLabel::MovementMode);
+                    children
+                        .button("Change")
+                        .insert(OptionsAction::CycleMovementMode);
+                });
+        }
+    }
+}
+
+/// A horizontal container for widgets.
+fn row() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            align_items: AlignItems::Center,
+            column_gap: Px(10.0),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+fn handle_options_action(
+    mut commands: Commands,
+    screen: Res<State<Screen>>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut next_playing_state: ResMut<NextState<PlayingState>>,
+    mut graphics: ResMut<GraphicsSettings>,
+    mut volumes: ResMut<AudioVolumes>,
+    mut bindings: ResMut<InputBindings>,
+    mut controls: ResMut<ControlsMenu>,
+    mut movement_mode: ResMut<MovementMode>,
+    frame: Res<FrameCount>,
+    content: Query<Entity, With<OptionsContent>>,
+    mut button_query: InteractionQuery<&OptionsAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match *action {
+                OptionsAction::ShowTab(tab) => {
+                    for content in &content {
+                        commands
+                            .entity(content)
+                            .despawn_descendants()
+                            .with_children(|children| spawn_tab(children, tab));
+                    }
+                }
+                OptionsAction::CycleGraphics(option) => option.cycle(&mut graphics),
+                OptionsAction::ChangeVolume(bus, change) => {
+                    // Stick to round steps.
+                    let volume = ((volumes.get(bus) + change) / VOLUME_STEP).round() * VOLUME_STEP;
+                    volumes.set(bus, volume);
+                }
+                OptionsAction::CycleAction(offset) => {
+                    controls.cycle_action(offset);
+                    controls.message.clear();
+                }
+                OptionsAction::CycleSlot(offset) => {
+                    controls.cycle_slot(&bindings, offset);
+                    controls.message.clear();
+                }
+                OptionsAction::Rebind => {
+                    controls.rebinding = Some(frame.0);
+                    controls.message =
+                        "Press a key or a gamepad button, or Escape to cancel".to_string();
+                }
+                OptionsAction::RemoveBinding => {
+                    let action = controls.action();
+                    if let Some(&binding) = bindings.bindings(action).get(controls.slot) {
+                        bindings.unbind(action, binding);
+                        controls.message = format!("{} unbound", binding.name());
+                    }
+                }
+                OptionsAction::ResetBindings => {
+                    *bindings = InputBindings::default();
+                    controls.slot = 0;
+                    controls.message = "Controls reset to the defaults".to_string();
+                }
+                OptionsAction::CycleMovementMode => {
+                    *movement_mode = match *movement_mode {
+                        MovementMode::Tank => MovementMode::CameraRelative,
+                        MovementMode::CameraRelative => MovementMode::Tank,
+                    };
+                }
+                // Go back to wherever the options were opened from.
+                OptionsAction::Back => match screen.get() {
+                    Screen::Playing => next_playing_state.set(PlayingState::Paused),
+                    _ => next_screen.set(Screen::Title),
+                },
+            }
+        }
+    }
+}
+
+/// Bind the next key or gamepad button pressed to the picked slot.
+fn capture_binding(
+    frame: Res<FrameCount>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    mut bindings: ResMut<InputBindings>,
+    mut controls: ResMut<ControlsMenu>,
+) {
+    // The input that started the rebinding, e.g. a gamepad button pressing the Rebind button,
+    // isn't the one to bind.
+    if controls.rebinding == Some(frame.0) {
+        return;
+    }
+    let binding = match keys.get_just_pressed().next() {
+        Some(&key) => Binding::Key(key),
+        None => match gamepad_buttons.get_just_pressed().next() {
+            Some(button) => Binding::GamepadButton(button.button_type),
+            None => return,
+        },
+    };
+    controls.rebinding = None;
+    if binding == Binding::Key(KeyCode::Escape) {
+        controls.message.clear();
+        return;
+    }
+
+    let action = controls.action();
+    controls.message = match bindings.replace(action, controls.slot, binding) {
+        Ok(()) => format!("{} bound to {}", action.name(), binding.name()),
+        Err(conflict) => format!(
+            "{} is already bound to {}",
+            binding.name(),
+            conflict.action.name()
+        ),
+    };
+}
+
+fn update_option_labels(
+    graphics: Res<GraphicsSettings>,
+    volumes: Res<AudioVolumes>,
+    bindings: Res<InputBindings>,
+    controls: Res<ControlsMenu>,
+    movement_mode: Res<MovementMode>,
+    labels: Query<(&OptionLabel, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (label, children) in &labels {
+        let value = match *label {
+            OptionLabel::Graphics(option) => option.describe(&graphics),
+            OptionLabel::Volume(bus) => {
+                format!("{}: {:.0}%", bus.name(), volumes.get(bus) * 100.0)
+            }
+            OptionLabel::Action => controls.action().name().to_string(),
+            OptionLabel::Slot => match bindings.bindings(controls.action()).get(controls.slot) {
+                Some(binding) => binding.name(),
+                None => "New input".to_string(),
+            },
+            OptionLabel::Bindings => {
+                let names = bindings
+                    .bindings(controls.action())
+                    .iter()
+                    .map(|binding| binding.name())
+                    .collect::<Vec<_>>();
+                if names.is_empty() {
+                    "Not bound".to_string()
+                } else {
+                    names.join(", ")
+                }
+            }
+            OptionLabel::ControlsMessage => controls.message.clone(),
+            OptionLabel::MovementMode => match *movement_mode {
+                MovementMode::Tank => "Movement: Tank".to_string(),
+                MovementMode::CameraRelative => "Movement: Camera-relative".to_string(),
+            },
+        };
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            if text.sections[0].value != value {
+                text.sections[0].value.clone_from(&value);
+            }
+        }
+    }
+}
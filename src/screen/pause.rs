@@ -0,0 +1,123 @@
+//! The pause menu, shown over the game while playing.
+
+use avian3d::prelude::{Physics, PhysicsTime};
+use bevy::prelude::*;
+
+use super::{options, PlayingState, Screen};
+use crate::{
+    game::{audio::soundtrack::SoundtrackDucking, spawn::level::RestartLevel},
+    input::{Action, ActionState},
+    ui::prelude::*,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(PlayingState::Paused), enter_pause_menu);
+    app.add_systems(OnExit(PlayingState::Running), pause);
+    app.add_systems(OnEnter(PlayingState::Running), unpause);
+    // Quitting from the pause menu doesn't go back to running.
+    app.add_systems(OnExit(Screen::Playing), unpause);
+
+    app.register_type::<PauseAction>();
+    app.add_systems(
+        Update,
+        (
+            // Escape cancels a rebinding in the options rather than closing them.
+            toggle_pause
+                .in_set(AppSet::Update)
+                .run_if(in_state(Screen::Playing).and_then(not(options::rebinding))),
+            handle_pause_action.run_if(in_state(PlayingState::Paused)),
+        ),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum PauseAction {
+    Resume,
+    Save,
+    Options,
+    Restart,
+    Quit,
+}
+
+fn enter_pause_menu(mut commands: Commands) {
+    commands
+        .ui_root()
+        .insert((
+            StateScoped(PlayingState::Paused),
+            BackgroundColor(ui_palette::OVERLAY_BACKGROUND),
+        ))
+        .with_children(|children| {
+            children.header("Paused");
+            children.button("Resume").insert(PauseAction::Resume);
+            children.button("Save game").insert(PauseAction::Save);
+            children.button("Options").insert(PauseAction::Options);
+            children
+                .button("Restart level")
+                .insert(PauseAction::Restart);
+            children.button("Quit to title").insert(PauseAction::Quit);
+        });
+}
+
+/// Freeze the game and duck the soundtrack.
+fn pause(
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut physics_time: ResMut<Time<Physics>>,
+    mut ducking: ResMut<SoundtrackDucking>,
+) {
+    virtual_time.pause();
+    physics_time.pause();
+    ducking.ducked = true;
+}
+
+fn unpause(
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut physics_time: ResMut<Time<Physics>>,
+    mut ducking: ResMut<SoundtrackDucking>,
+) {
+    virtual_time.unpause();
+    physics_time.unpause();
+    ducking.ducked = false;
+}
+
+/// Pause or resume with [`Action::Pause`], going back from the options or the saves to the pause
+/// menu.
+fn toggle_pause(
+    actions: Res<ActionState>,
+    state: Res<State<PlayingState>>,
+    mut next_state: ResMut<NextState<PlayingState>>,
+) {
+    if !actions.just_pressed(Action::Pause) {
+        return;
+    }
+    next_state.set(match state.get() {
+        PlayingState::Running => PlayingState::Paused,
+        PlayingState::Paused => PlayingState::Running,
+        PlayingState::Options | PlayingState::Save => PlayingState::Paused,
+        // The level is over, there's nothing to resume.
+        PlayingState::Complete => return,
+    });
+}
+
+fn handle_pause_action(
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut next_state: ResMut<NextState<PlayingState>>,
+    mut button_query: InteractionQuery<&PauseAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                PauseAction::Resume => next_state.set(PlayingState::Running),
+                PauseAction::Save => next_state.set(PlayingState::Save),
+                PauseAction::Options => next_state.set(PlayingState::Options),
+                PauseAction::Restart => {
+                    commands.trigger(RestartLevel);
+                    next_state.set(PlayingState::Running);
+                }
+                PauseAction::Quit => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
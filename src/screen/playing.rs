@@ -1,34 +1,32 @@
 //! The screen state for the main game loop.
 
-use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+use bevy::prelude::*;
 
 use super::Screen;
-use crate::game::{audio::soundtrack::PlaySoundtrack, spawn::level::SpawnLevel};
+use crate::game::{
+    audio::soundtrack::PlaySoundtrack,
+    spawn::level::{SelectedLevel, SpawnLevel},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Playing), enter_playing);
     app.add_systems(OnExit(Screen::Playing), exit_playing);
-
-    app.add_systems(
-        Update,
-        return_to_title_screen
-            .run_if(in_state(Screen::Playing).and_then(input_just_pressed(KeyCode::Escape))),
-    );
 }
 
-fn enter_playing(mut commands: Commands) {
+fn enter_playing(mut commands: Commands, selected_level: Res<SelectedLevel>) {
+    // Only two soundtracks ship with the game, so the credits theme is played during the game
+    // too, rather than looping the gameplay one forever.
     #[cfg(not(feature = "dev"))]
-    commands.trigger(PlaySoundtrack::Key(
-        crate::game::assets::SoundtrackKey::Gameplay,
-    ));
-    commands.trigger(SpawnLevel);
+    {
+        use crate::game::{assets::SoundtrackKey, audio::soundtrack::Playlist};
+        commands.trigger(PlaySoundtrack::Playlist(
+            Playlist::new([SoundtrackKey::Gameplay, SoundtrackKey::Credits]).shuffled(),
+        ));
+    }
+    commands.trigger(SpawnLevel(selected_level.0));
 }
 
 fn exit_playing(mut commands: Commands) {
     // We could use [`StateScoped`] on the sound playing entities instead.
     commands.trigger(PlaySoundtrack::Disable);
 }
-
-fn return_to_title_screen(mut next_screen: ResMut<NextState<Screen>>) {
-    next_screen.set(Screen::Title);
-}
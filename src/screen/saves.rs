@@ -0,0 +1,157 @@
+//! Pick a save slot: to load a game from the title screen, or to save the game from the pause
+//! menu.
+
+use bevy::{prelude::*, ui::Val::*};
+
+use super::{PlayingState, Screen};
+use crate::{
+    game::save::{LoadGame, SaveGame, SaveHeader, SaveSlot},
+    ui::{format, prelude::*},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::LoadGame), enter_load_menu);
+    app.add_systems(OnEnter(PlayingState::Save), enter_save_menu);
+
+    app.register_type::<SavesAction>();
+    app.add_systems(
+        Update,
+        (
+            handle_saves_action,
+            update_slot_labels.run_if(resource_changed::<SlotHeaders>),
+        )
+            .chain()
+            .run_if(in_state(Screen::LoadGame).or_else(in_state(PlayingState::Save))),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum SavesAction {
+    Load(SaveSlot),
+    Save(SaveSlot),
+    Back,
+}
+
+/// Describes the save in a slot.
+#[derive(Component, Debug)]
+struct SlotLabel(SaveSlot);
+
+/// The headers of the saves in every slot, read when opening the menu and after saving.
+#[derive(Resource, Debug)]
+struct SlotHeaders(Vec<(SaveSlot, Option<SaveHeader>)>);
+
+impl SlotHeaders {
+    fn read() -> Self {
+        Self(
+            SaveSlot::ALL
+                .into_iter()
+                .map(|slot| (slot, slot.header()))
+                .collect(),
+        )
+    }
+}
+
+fn enter_load_menu(mut commands: Commands) {
+    commands.insert_resource(SlotHeaders::read());
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::LoadGame))
+        .with_children(|children| {
+            children.header("Load game");
+            for slot in SaveSlot::ALL {
+                spawn_slot_row(children, slot, SavesAction::Load(slot));
+            }
+            children.button("Back").insert(SavesAction::Back);
+        });
+}
+
+fn enter_save_menu(mut commands: Commands) {
+    commands.insert_resource(SlotHeaders::read());
+    commands
+        .ui_root()
+        .insert((
+            StateScoped(PlayingState::Save),
+            BackgroundColor(ui_palette::OVERLAY_BACKGROUND),
+        ))
+        .with_children(|children| {
+            children.header("Save game");
+            for slot in SaveSlot::MANUAL {
+                spawn_slot_row(children, slot, SavesAction::Save(slot));
+            }
+            children.button("Back").insert(SavesAction::Back);
+        });
+}
+
+fn spawn_slot_row(children: &mut ChildBuilder, slot: SaveSlot, action: SavesAction) {
+    children
+        .spawn((
+            Name::new(format!("{} Row", slot.name())),
+            NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    column_gap: Px(10.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children.button(slot.name()).insert(action);
+            children.label("").insert(SlotLabel(slot));
+        });
+}
+
+fn handle_saves_action(
+    mut commands: Commands,
+    screen: Res<State<Screen>>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut next_playing_state: ResMut<NextState<PlayingState>>,
+    mut button_query: InteractionQuery<&SavesAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match *action {
+                SavesAction::Load(slot) => commands.trigger(LoadGame(slot)),
+                SavesAction::Save(slot) => {
+                    commands.trigger(SaveGame(slot));
+                    // Read the headers after the save has been written.
+                    commands.add(|world: &mut World| world.insert_resource(SlotHeaders::read()));
+                }
+                // Go back to wherever the saves were opened from.
+                SavesAction::Back => match screen.get() {
+                    Screen::Playing => next_playing_state.set(PlayingState::Paused),
+                    _ => next_screen.set(Screen::Title),
+                },
+            }
+        }
+    }
+}
+
+fn update_slot_labels(
+    headers: Res<SlotHeaders>,
+    mut labels: Query<(&SlotLabel, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (label, children) in &mut labels {
+        let header = headers
+            .0
+            .iter()
+            .find(|(slot, _)| *slot == label.0)
+            .and_then(|(_, header)| header.as_ref());
+        let value = match header {
+            Some(header) => format!(
+                "{} - {}",
+                header.level_name,
+                format::duration(header.play_time)
+            ),
+            None => "Empty".to_string(),
+        };
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            if text.sections[0].value != value {
+                text.sections[0].value.clone_from(&value);
+            }
+        }
+    }
+}
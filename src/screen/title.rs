@@ -3,7 +3,10 @@
 use bevy::prelude::*;
 
 use super::Screen;
-use crate::ui::prelude::*;
+use crate::{
+    game::{assets::LevelId, spawn::level::SelectedLevel},
+    ui::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Title), enter_title);
@@ -16,6 +19,9 @@ pub(super) fn plugin(app: &mut App) {
 #[reflect(Component)]
 enum TitleAction {
     Play,
+    Levels,
+    LoadGame,
+    Options,
     Credits,
     /// Exit doesn't work well with embedded applications.
     #[cfg(not(target_family = "wasm"))]
@@ -28,6 +34,9 @@ fn enter_title(mut commands: Commands) {
         .insert(StateScoped(Screen::Title))
         .with_children(|children| {
             children.button("Play").insert(TitleAction::Play);
+            children.button("Levels").insert(TitleAction::Levels);
+            children.button("Load game").insert(TitleAction::LoadGame);
+            children.button("Options").insert(TitleAction::Options);
             children.button("Credits").insert(TitleAction::Credits);
 
             #[cfg(not(target_family = "wasm"))]
@@ -36,6 +45,7 @@ fn enter_title(mut commands: Commands) {
 }
 
 fn handle_title_action(
+    mut selected_level: ResMut<SelectedLevel>,
     mut next_screen: ResMut<NextState<Screen>>,
     mut button_query: InteractionQuery<&TitleAction>,
     #[cfg(not(target_family = "wasm"))] mut app_exit: EventWriter<AppExit>,
@@ -43,7 +53,13 @@ fn handle_title_action(
     for (interaction, action) in &mut button_query {
         if matches!(interaction, Interaction::Pressed) {
             match action {
-                TitleAction::Play => next_screen.set(Screen::Playing),
+                TitleAction::Play => {
+                    selected_level.0 = LevelId::default();
+                    next_screen.set(Screen::CharacterSelect);
+                }
+                TitleAction::Levels => next_screen.set(Screen::LevelSelect),
+                TitleAction::LoadGame => next_screen.set(Screen::LoadGame),
+                TitleAction::Options => next_screen.set(Screen::Options),
                 TitleAction::Credits => next_screen.set(Screen::Credits),
 
                 #[cfg(not(target_family = "wasm"))]
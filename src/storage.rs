@@ -0,0 +1,88 @@
+//! Persist small pieces of user data (bindings, settings...) as RON between runs: in files in the
+//! config directory on native, and in the local storage of the page on the web.
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Load the value stored under `name`, if any.
+pub fn load<T: DeserializeOwned>(name: &str) -> Option<T> {
+    let text = read(name)?;
+    match ron::de::from_str(&text) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warn!("Ignoring invalid {name} file: {err}");
+            None
+        }
+    }
+}
+
+/// Store `value` under `name`, overwriting the previous value.
+pub fn save<T: Serialize>(name: &str, value: &T) {
+    match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+        Ok(text) => write(name, &text),
+        Err(err) => error!("Could not serialize {name}: {err}"),
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn path(name: &str) -> Option<std::path::PathBuf> {
+    let dirs = directories::ProjectDirs::from("", "", "bevy3dtest")?;
+    Some(dirs.config_dir().join(format!("{name}.ron")))
+}
+
+/// Read the raw text stored under `name`, if any.
+#[cfg(not(target_family = "wasm"))]
+pub fn read(name: &str) -> Option<String> {
+    let path = path(name)?;
+    match std::fs::read_to_string(&path) {
+        Ok(text) => Some(text),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => {
+            warn!("Could not read {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+/// Store `text` under `name` as is.
+#[cfg(not(target_family = "wasm"))]
+pub fn write(name: &str, text: &str) {
+    let Some(path) = path(name) else {
+        warn!("Could not find a config directory to save {name} in");
+        return;
+    };
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, text));
+    if let Err(err) = result {
+        error!("Could not write {}: {err}", path.display());
+    }
+}
+
+// There's no file system on the web, use the local storage of the page instead.
+#[cfg(target_family = "wasm")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+#[cfg(target_family = "wasm")]
+fn key(name: &str) -> String {
+    format!("bevy3dtest.{name}")
+}
+
+#[cfg(target_family = "wasm")]
+pub fn read(name: &str) -> Option<String> {
+    local_storage()?.get_item(&key(name)).ok().flatten()
+}
+
+#[cfg(target_family = "wasm")]
+pub fn write(name: &str, text: &str) {
+    let Some(storage) = local_storage() else {
+        warn!("Local storage is not available to save {name} in");
+        return;
+    };
+    if let Err(err) = storage.set_item(&key(name), text) {
+        error!("Could not write {name} to local storage: {err:?}");
+    }
+}
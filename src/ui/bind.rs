@@ -0,0 +1,198 @@
+//! Widgets that update themselves from the value of a resource or a component.
+//!
+//! Bind a widget by inserting [`BindText`], [`BindProgress`] or [`BindDisplay`] on it, with the
+//! type it reads from, and register that type once with [`BindAppExt`]. Bound widgets are updated
+//! when the value changes and when they're spawned.
+
+use bevy::{prelude::*, ui::Val::*};
+
+use super::widgets::ProgressBarFill;
+use crate::AppSet;
+
+/// Sets the text of the widget, i.e. of the [`Text`] children of its entity.
+#[derive(Component)]
+pub struct BindText<S> {
+    format: Box<dyn Fn(&S) -> String + Send + Sync>,
+}
+
+impl<S> BindText<S> {
+    pub fn new(format: impl Fn(&S) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            format: Box::new(format),
+        }
+    }
+}
+
+/// Sets how full a [`Widgets::progress_bar`](super::widgets::Widgets::progress_bar) is, from 0
+/// to 1.
+#[derive(Component)]
+pub struct BindProgress<S> {
+    fraction: Box<dyn Fn(&S) -> f32 + Send + Sync>,
+}
+
+impl<S> BindProgress<S> {
+    pub fn new(fraction: impl Fn(&S) -> f32 + Send + Sync + 'static) -> Self {
+        Self {
+            fraction: Box::new(fraction),
+        }
+    }
+}
+
+/// Shows the widget only when `shown` returns true.
+#[derive(Component)]
+pub struct BindDisplay<S> {
+    shown: Box<dyn Fn(&S) -> bool + Send + Sync>,
+}
+
+impl<S> BindDisplay<S> {
+    pub fn new(shown: impl Fn(&S) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            shown: Box::new(shown),
+        }
+    }
+}
+
+pub trait BindAppExt {
+    /// Update the widgets bound to the resource `R`.
+    fn bind_resource<R: Resource>(&mut self) -> &mut Self;
+
+    /// Update the widgets bound to the component `C`, read from the only entity that has it.
+    /// The widgets are hidden while there's no such entity.
+    fn bind_component<C: Component>(&mut self) -> &mut Self;
+}
+
+impl BindAppExt for App {
+    fn bind_resource<R: Resource>(&mut self) -> &mut Self {
+        self.add_systems(Update, update_resource_widgets::<R>.after(AppSet::Update))
+    }
+
+    fn bind_component<C: Component>(&mut self) -> &mut Self {
+        self.add_systems(Update, update_component_widgets::<C>.after(AppSet::Update))
+    }
+}
+
+type BoundWidgets<'w, 's, S> = Query<
+    'w,
+    's,
+    (
+        Option<Ref<'static, BindText<S>>>,
+        Option<Ref<'static, BindProgress<S>>>,
+        Option<Ref<'static, BindDisplay<S>>>,
+        Option<&'static Children>,
+        &'static mut Style,
+    ),
+    (
+        Or<(
+            With<BindText<S>>,
+            With<BindProgress<S>>,
+            With<BindDisplay<S>>,
+        )>,
+        Without<ProgressBarFill>,
+    ),
+>;
+
+type ProgressBarFills<'w, 's> = Query<'w, 's, &'static mut Style, With<ProgressBarFill>>;
+
+fn update_resource_widgets<R: Resource>(
+    resource: Option<Res<R>>,
+    mut widgets: BoundWidgets<R>,
+    mut texts: Query<&mut Text>,
+    mut fills: ProgressBarFills,
+) {
+    let Some(resource) = resource else {
+        return;
+    };
+    update_widgets(
+        &*resource,
+        resource.is_changed(),
+        &mut widgets,
+        &mut texts,
+        &mut fills,
+    );
+}
+
+fn update_component_widgets<C: Component>(
+    components: Query<Ref<C>>,
+    // Whether there was a component to read from during the last update.
+    mut found: Local<bool>,
+    mut widgets: BoundWidgets<C>,
+    mut texts: Query<&mut Text>,
+    mut fills: ProgressBarFills,
+) {
+    let component = components.get_single().ok();
+    let appeared_or_gone = component.is_some() != *found;
+    *found = component.is_some();
+    match component {
+        Some(component) => update_widgets(
+            &*component,
+            component.is_changed() || appeared_or_gone,
+            &mut widgets,
+            &mut texts,
+            &mut fills,
+        ),
+        None => {
+            for (text, progress, display, _, mut style) in &mut widgets {
+                if appeared_or_gone || is_added(&text, &progress, &display) {
+                    set_display(&mut style, false);
+                }
+            }
+        }
+    }
+}
+
+fn update_widgets<S: Send + Sync + 'static>(
+    value: &S,
+    changed: bool,
+    widgets: &mut BoundWidgets<S>,
+    texts: &mut Query<&mut Text>,
+    fills: &mut ProgressBarFills,
+) {
+    for (text, progress, display, children, mut style) in widgets {
+        // Widgets that were just spawned are out of date too.
+        if !changed && !is_added(&text, &progress, &display) {
+            continue;
+        }
+        set_display(
+            &mut style,
+            display.is_none_or(|display| (display.shown)(value)),
+        );
+        let Some(children) = children else {
+            continue;
+        };
+        if let Some(text) = text {
+            let value = (text.format)(value);
+            let mut texts = texts.iter_many_mut(children);
+            while let Some(mut text) = texts.fetch_next() {
+                if text.sections[0].value != value {
+                    text.sections[0].value.clone_from(&value);
+                }
+            }
+        }
+        if let Some(progress) = progress {
+            let width = Percent(100.0 * (progress.fraction)(value).clamp(0.0, 1.0));
+            let mut fills = fills.iter_many_mut(children);
+            while let Some(mut fill) = fills.fetch_next() {
+                if fill.width != width {
+                    fill.width = width;
+                }
+            }
+        }
+    }
+}
+
+fn is_added<S: Send + Sync + 'static>(
+    text: &Option<Ref<BindText<S>>>,
+    progress: &Option<Ref<BindProgress<S>>>,
+    display: &Option<Ref<BindDisplay<S>>>,
+) -> bool {
+    text.as_ref().is_some_and(Ref::is_added)
+        || progress.as_ref().is_some_and(Ref::is_added)
+        || display.as_ref().is_some_and(Ref::is_added)
+}
+
+fn set_display(style: &mut Mut<Style>, shown: bool) {
+    let display = if shown { Display::Flex } else { Display::None };
+    if style.display != display {
+        style.display = display;
+    }
+}
@@ -0,0 +1,32 @@
+//! Formatting of values shown in the UI.
+
+use std::time::Duration;
+
+/// Format as `m:ss`, or `h:mm:ss` from an hour.
+pub fn duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 3600 {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    } else {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_under_an_hour() {
+        assert_eq!(duration(Duration::ZERO), "0:00");
+        assert_eq!(duration(Duration::from_millis(65_900)), "1:05");
+        assert_eq!(duration(Duration::from_secs(3599)), "59:59");
+    }
+
+    #[test]
+    fn duration_from_an_hour() {
+        assert_eq!(duration(Duration::from_secs(3600)), "1:00:00");
+        assert_eq!(duration(Duration::from_secs(3661)), "1:01:01");
+        assert_eq!(duration(Duration::from_secs(36_000 + 59 * 60)), "10:59:00");
+    }
+}
@@ -1,6 +1,10 @@
 use bevy::prelude::*;
 
-use crate::game::{assets::SfxKey, audio::sfx::PlaySfx};
+use super::navigation::MenuFocus;
+use crate::game::{
+    assets::SfxKey,
+    audio::sfx::{PlaySfx, Sfx},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<InteractionPalette>();
@@ -20,10 +24,21 @@ pub struct InteractionPalette {
 }
 
 fn apply_interaction_palette(
-    mut palette_query: InteractionQuery<(&InteractionPalette, &mut BackgroundColor)>,
+    focus: Res<MenuFocus>,
+    mut palette_query: Query<(
+        Entity,
+        Ref<Interaction>,
+        &InteractionPalette,
+        &mut BackgroundColor,
+    )>,
 ) {
-    for (interaction, (palette, mut background)) in &mut palette_query {
-        *background = match interaction {
+    for (entity, interaction, palette, mut background) in &mut palette_query {
+        if !interaction.is_changed() && !focus.is_changed() {
+            continue;
+        }
+        *background = match *interaction {
+            // Buttons focused with a gamepad look hovered.
+            Interaction::None if focus.0 == Some(entity) => palette.hovered,
             Interaction::None => palette.none,
             Interaction::Hovered => palette.hovered,
             Interaction::Pressed => palette.pressed,
@@ -38,8 +53,8 @@ fn trigger_interaction_sfx(
 ) {
     for interaction in &mut interactions {
         match interaction {
-            Interaction::Hovered => commands.trigger(PlaySfx::Key(SfxKey::ButtonHover)),
-            Interaction::Pressed => commands.trigger(PlaySfx::Key(SfxKey::ButtonPress)),
+            Interaction::Hovered => commands.trigger(PlaySfx::new(Sfx::Key(SfxKey::ButtonHover))),
+            Interaction::Pressed => commands.trigger(PlaySfx::new(Sfx::Key(SfxKey::ButtonPress))),
             _ => (),
         }
     }
@@ -3,20 +3,24 @@
 // Unused utilities and re-exports may trigger these lints undesirably.
 #![allow(dead_code, unused_imports)]
 
+pub mod bind;
+pub mod format;
 pub mod interaction;
+pub mod navigation;
 pub mod palette;
 mod widgets;
 
 pub mod prelude {
     pub use super::{
+        bind::{BindAppExt as _, BindDisplay, BindProgress, BindText},
         interaction::{InteractionPalette, InteractionQuery},
         palette as ui_palette,
-        widgets::{Containers as _, Widgets as _},
+        widgets::{Containers as _, HudAnchor, Widgets as _},
     };
 }
 
 use bevy::prelude::*;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins(interaction::plugin);
+    app.add_plugins((interaction::plugin, navigation::plugin));
 }
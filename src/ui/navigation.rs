@@ -0,0 +1,80 @@
+//! Gamepad navigation of button menus.
+//!
+//! The D-pad moves the focus between the buttons of the current menu and the south face button
+//! presses the focused one, so screens only need to handle [`Interaction::Pressed`] as usual.
+
+use bevy::{prelude::*, ui::UiSystem};
+
+use super::interaction::InteractionPalette;
+use crate::game::{
+    assets::SfxKey,
+    audio::sfx::{PlaySfx, Sfx},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<MenuFocus>();
+    app.add_systems(PreUpdate, navigate_menu.after(UiSystem::Focus));
+}
+
+/// The button currently focused with a gamepad, if any.
+#[derive(Resource, Debug, Default)]
+pub struct MenuFocus(pub Option<Entity>);
+
+fn navigate_menu(
+    mut commands: Commands,
+    mut focus: ResMut<MenuFocus>,
+    // The button pressed with a gamepad on the last frame, to be released on this one.
+    mut pressed: Local<Option<Entity>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    mut buttons: Query<(Entity, &GlobalTransform, &mut Interaction), With<InteractionPalette>>,
+) {
+    if let Some((_, _, mut interaction)) = pressed.take().and_then(|e| buttons.get_mut(e).ok()) {
+        *interaction = Interaction::None;
+    }
+
+    let just_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+    let step: isize = match (
+        just_pressed(GamepadButtonType::DPadUp),
+        just_pressed(GamepadButtonType::DPadDown),
+    ) {
+        (true, false) => -1,
+        (false, true) => 1,
+        _ => 0,
+    };
+
+    // Order buttons as they appear on screen, from top to bottom.
+    let mut order: Vec<(Entity, Vec3)> = buttons
+        .iter()
+        .map(|(entity, transform, _)| (entity, transform.translation()))
+        .collect();
+    order.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    let current = focus
+        .0
+        .and_then(|focused| order.iter().position(|(entity, _)| *entity == focused));
+    if current.is_none() && focus.0.is_some() {
+        // The focused button is gone, e.g. because the screen changed.
+        focus.0 = None;
+    }
+
+    if step != 0 && !order.is_empty() {
+        let next = match current {
+            Some(index) => (index as isize + step).rem_euclid(order.len() as isize) as usize,
+            None => 0,
+        };
+        focus.0 = Some(order[next].0);
+        commands.trigger(PlaySfx::new(Sfx::Key(SfxKey::ButtonHover)));
+    }
+
+    if just_pressed(GamepadButtonType::South) {
+        if let Some((entity, _, mut interaction)) = focus.0.and_then(|e| buttons.get_mut(e).ok()) {
+            *interaction = Interaction::Pressed;
+            *pressed = Some(entity);
+        }
+    }
+}
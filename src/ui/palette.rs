@@ -8,3 +8,10 @@ pub const LABEL_TEXT: Color = Color::srgb(0.867, 0.827, 0.412);
 pub const HEADER_TEXT: Color = Color::srgb(0.867, 0.827, 0.412);
 
 pub const NODE_BACKGROUND: Color = Color::srgb(0.286, 0.478, 0.773);
+
+/// Behind menus shown over the game, e.g. when paused.
+pub const OVERLAY_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+
+/// Behind the HUD, which shouldn't hide too much of the game.
+pub const HUD_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.4);
+pub const HUD_TEXT: Color = Color::srgb(0.925, 0.925, 0.925);
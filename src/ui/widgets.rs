@@ -14,8 +14,24 @@ pub trait Widgets {
 
     /// Spawn a simple text label.
     fn label(&mut self, text: impl Into<String>) -> EntityCommands;
+
+    /// Spawn a text over a translucent background, sized to fit, for the HUD.
+    fn hud_label(&mut self, text: impl Into<String>) -> EntityCommands;
+
+    /// Spawn a small colored icon followed by a text, e.g. to count something in the HUD.
+    fn counter(&mut self, icon: Color, text: impl Into<String>) -> EntityCommands;
+
+    /// Spawn a small colored square.
+    fn icon(&mut self, color: Color) -> EntityCommands;
+
+    /// Spawn an empty bar, filled with `color` by [`BindProgress`](super::bind::BindProgress).
+    fn progress_bar(&mut self, color: Color) -> EntityCommands;
 }
 
+/// The part of a [`Widgets::progress_bar`] that fills up.
+#[derive(Component, Debug)]
+pub struct ProgressBarFill;
+
 impl<T: Spawn> Widgets for T {
     fn button(&mut self, text: impl Into<String>) -> EntityCommands {
         let mut entity = self.spawn((
@@ -112,6 +128,112 @@ impl<T: Spawn> Widgets for T {
         });
         entity
     }
+
+    fn hud_label(&mut self, text: impl Into<String>) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("HUD Label"),
+            NodeBundle {
+                style: Style {
+                    padding: UiRect::axes(Px(10.0), Px(4.0)),
+                    ..default()
+                },
+                background_color: BackgroundColor(HUD_BACKGROUND),
+                ..default()
+            },
+        ));
+        entity.with_children(|children| {
+            children.spawn((
+                Name::new("HUD Label Text"),
+                TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font_size: 24.0,
+                        color: HUD_TEXT,
+                        ..default()
+                    },
+                ),
+            ));
+        });
+        entity
+    }
+
+    fn counter(&mut self, icon: Color, text: impl Into<String>) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Counter"),
+            NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    column_gap: Px(8.0),
+                    padding: UiRect::axes(Px(10.0), Px(4.0)),
+                    ..default()
+                },
+                background_color: BackgroundColor(HUD_BACKGROUND),
+                ..default()
+            },
+        ));
+        entity.with_children(|children| {
+            children.icon(icon);
+            children.spawn((
+                Name::new("Counter Text"),
+                TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font_size: 24.0,
+                        color: HUD_TEXT,
+                        ..default()
+                    },
+                ),
+            ));
+        });
+        entity
+    }
+
+    fn icon(&mut self, color: Color) -> EntityCommands {
+        self.spawn((
+            Name::new("Icon"),
+            NodeBundle {
+                style: Style {
+                    width: Px(16.0),
+                    height: Px(16.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(color),
+                ..default()
+            },
+        ))
+    }
+
+    fn progress_bar(&mut self, color: Color) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Progress Bar"),
+            NodeBundle {
+                style: Style {
+                    width: Px(200.0),
+                    height: Px(12.0),
+                    padding: UiRect::all(Px(2.0)),
+                    ..default()
+                },
+                background_color: BackgroundColor(HUD_BACKGROUND),
+                ..default()
+            },
+        ));
+        entity.with_children(|children| {
+            children.spawn((
+                Name::new("Progress Bar Fill"),
+                ProgressBarFill,
+                NodeBundle {
+                    style: Style {
+                        width: Percent(0.0),
+                        height: Percent(100.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(color),
+                    ..default()
+                },
+            ));
+        });
+        entity
+    }
 }
 
 /// An extension trait for spawning UI containers.
@@ -119,6 +241,43 @@ pub trait Containers {
     /// Spawns a root node that covers the full screen
     /// and centers its content horizontally and vertically.
     fn ui_root(&mut self) -> EntityCommands;
+
+    /// Spawns a root node that covers the full screen and stacks its content at `anchor`, over
+    /// the game.
+    fn hud(&mut self, anchor: HudAnchor) -> EntityCommands;
+}
+
+/// Where the content of a [`Containers::hud`] is placed on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HudAnchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl HudAnchor {
+    /// How the content is justified vertically, then horizontally.
+    fn alignment(self) -> (JustifyContent, AlignItems) {
+        let vertical = match self {
+            HudAnchor::TopLeft | HudAnchor::Top | HudAnchor::TopRight => JustifyContent::FlexStart,
+            HudAnchor::Left | HudAnchor::Center | HudAnchor::Right => JustifyContent::Center,
+            HudAnchor::BottomLeft | HudAnchor::Bottom | HudAnchor::BottomRight => {
+                JustifyContent::FlexEnd
+            }
+        };
+        let horizontal = match self {
+            HudAnchor::TopLeft | HudAnchor::Left | HudAnchor::BottomLeft => AlignItems::FlexStart,
+            HudAnchor::Top | HudAnchor::Center | HudAnchor::Bottom => AlignItems::Center,
+            HudAnchor::TopRight | HudAnchor::Right | HudAnchor::BottomRight => AlignItems::FlexEnd,
+        };
+        (vertical, horizontal)
+    }
 }
 
 impl Containers for Commands<'_, '_> {
@@ -140,6 +299,27 @@ impl Containers for Commands<'_, '_> {
             },
         ))
     }
+
+    fn hud(&mut self, anchor: HudAnchor) -> EntityCommands {
+        let (justify_content, align_items) = anchor.alignment();
+        self.spawn((
+            Name::new(format!("HUD {anchor:?}")),
+            NodeBundle {
+                style: Style {
+                    width: Percent(100.0),
+                    height: Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content,
+                    align_items,
+                    row_gap: Px(6.0),
+                    padding: UiRect::all(Px(16.0)),
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+    }
 }
 
 /// An internal trait for types that can spawn entities.